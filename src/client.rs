@@ -1,6 +1,9 @@
 use std::{net::Ipv4Addr, sync::Mutex};
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{collection::CollectionStatus, roster::Student, submission::Submission};
 
 #[derive(Debug)]
 pub struct Client {
@@ -8,6 +11,41 @@ pub struct Client {
     pub ip_address: Ipv4Addr,
     pub current_layer: Mutex<Option<String>>,
     pub last_timer_access: Mutex<Option<DateTime<Utc>>>,
+    pub last_heartbeat: Mutex<Option<DateTime<Utc>>>,
+    pub submissions: Mutex<Vec<Submission>>,
+    pub collection_status: Mutex<Option<CollectionStatus>>,
+    pub notes: Mutex<Vec<Note>>,
+    pub student: Mutex<Option<Student>>,
+    /// individual start of a late student, replaces the start of the room for this client
+    pub start_override: Mutex<Option<DateTime<Utc>>>,
+    /// the student handed in early at this time
+    pub finished: Mutex<Option<DateTime<Utc>>>,
+    /// name of the exam group, `None` for the default duration
+    pub group: Mutex<Option<String>>,
+    /// layer the supervisor wants, `current_layer` is brought back to it when it drifts
+    pub intended_layer: Mutex<Option<IntendedLayer>>,
+    /// drift from `intended_layer` that is being corrected, reported only once
+    pub layer_correction: Mutex<Option<LayerCorrection>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IntendedLayer {
+    pub layer: String,
+    pub since: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LayerCorrection {
+    pub drifted_to: String,
+    pub attempts: u32,
+    pub last_attempt: DateTime<Utc>,
+}
+
+/// Free text the supervisor attached to a client, e.g. an incident during the exam.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Note {
+    pub time: DateTime<Utc>,
+    pub text: String,
 }
 
 impl Client {
@@ -17,6 +55,34 @@ impl Client {
             ip_address: ip,
             current_layer: Mutex::new(None),
             last_timer_access: Mutex::new(None),
+            last_heartbeat: Mutex::new(None),
+            submissions: Mutex::new(Vec::new()),
+            collection_status: Mutex::new(None),
+            notes: Mutex::new(Vec::new()),
+            student: Mutex::new(None),
+            start_override: Mutex::new(None),
+            finished: Mutex::new(None),
+            group: Mutex::new(None),
+            intended_layer: Mutex::new(None),
+            layer_correction: Mutex::new(None),
+        }
+    }
+
+    pub fn intend_layer(&self, layer: &str) {
+        *self.intended_layer.lock().unwrap() = Some(IntendedLayer {
+            layer: layer.to_string(),
+            since: Utc::now(),
+        });
+        *self.layer_correction.lock().unwrap() = None;
+    }
+
+    /// The observed layer differs from the intended one.
+    pub fn layer_drifted(&self) -> bool {
+        let current_layer = self.current_layer.lock().unwrap();
+        let intended_layer = self.intended_layer.lock().unwrap();
+        match (current_layer.as_ref(), intended_layer.as_ref()) {
+            (Some(current_layer), Some(intended)) => *current_layer != intended.layer,
+            _ => false,
         }
     }
 }
@@ -0,0 +1,300 @@
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    client::Client, event_log::Severity, exam_record::RecordCategory, persistance::persist_session,
+    shared_data::SharedData,
+};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum CollectionStatus {
+    Running,
+    Collected {
+        time: DateTime<Utc>,
+        file_count: usize,
+    },
+    MissingDirectory,
+    Failed(String),
+}
+
+#[derive(Debug, Serialize)]
+struct Manifest {
+    client: String,
+    work_directory: String,
+    collected_at: DateTime<Utc>,
+    files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestEntry {
+    path: String,
+    size: u64,
+    sha256: String,
+}
+
+/// Snapshots the work directory of every client into `<archive_directory>/<timestamp>/`.
+///
+/// Does nothing if no collection is configured for the room or one is already running.
+pub fn start_collection(shared_data: Arc<SharedData>) {
+    if shared_data.collection_info.is_none() {
+        return;
+    }
+    // held while checking and marking the clients, so the key of the supervisor and the
+    // collection at time up cannot both start one
+    let mut last_collection = shared_data.last_collection.lock().unwrap();
+    if collection_running(&shared_data) {
+        return;
+    }
+    for client in &shared_data.clients {
+        *client.collection_status.lock().unwrap() = Some(CollectionStatus::Running);
+    }
+    // persisted so a restart after time up does not collect and overwrite a second time
+    *last_collection = Some(Utc::now());
+    drop(last_collection);
+    persist_session(&shared_data);
+    shared_data.record_event(
+        RecordCategory::Collection,
+        "collection of student work started".to_string(),
+    );
+    thread::spawn(move || collect_all(shared_data));
+}
+
+/// Starts a collection once the timer ran out, if the room is configured to do so.
+pub fn start_collection_thread(shared_data: Arc<SharedData>) {
+    thread::spawn(move || loop {
+        let collect_when_time_is_up = shared_data
+            .collection_info
+            .as_ref()
+            .is_some_and(|info| info.collect_when_time_is_up);
+        // late students are still working until the last individual finish
+        let finish_time = shared_data.latest_finish_time();
+        let last_collection = *shared_data.last_collection.lock().unwrap();
+        if let Some(finish_time) = finish_time {
+            let time_is_up = Utc::now() >= finish_time;
+            let collected_since = last_collection.is_some_and(|last| last >= finish_time);
+            if collect_when_time_is_up && time_is_up && !collected_since {
+                start_collection(shared_data.clone());
+            }
+        }
+        thread::sleep(Duration::from_secs(1));
+    });
+}
+
+fn collection_running(shared_data: &SharedData) -> bool {
+    shared_data.clients.iter().any(|client| {
+        matches!(
+            *client.collection_status.lock().unwrap(),
+            Some(CollectionStatus::Running)
+        )
+    })
+}
+
+fn collect_all(shared_data: Arc<SharedData>) {
+    let info = shared_data
+        .collection_info
+        .as_ref()
+        .expect("checked by start_collection");
+    let target_directory =
+        Path::new(&info.archive_directory).join(Local::now().format("%Y%m%d-%H%M%S").to_string());
+    for client in &shared_data.clients {
+        let work_directory = PathBuf::from(info.work_directory.replace("{client}", &client.name));
+        let status = collect_work(client, &work_directory, &target_directory);
+        let description = match &status {
+            CollectionStatus::Collected { file_count, .. } => {
+                format!("collected {} files of {}", file_count, client.name)
+            }
+            CollectionStatus::MissingDirectory => {
+                format!("work directory of {} is missing", client.name)
+            }
+            CollectionStatus::Failed(err) => {
+                format!("collecting work of {} failed: {}", client.name, err)
+            }
+            CollectionStatus::Running => unreachable!("collect_client is finished"),
+        };
+        match &status {
+            CollectionStatus::MissingDirectory => {
+                shared_data.log(Severity::Warning, "collection", description.clone())
+            }
+            CollectionStatus::Failed(_) => {
+                shared_data.log(Severity::Error, "collection", description.clone())
+            }
+            _ => {}
+        }
+        shared_data.record_event(RecordCategory::Collection, description);
+        *client.collection_status.lock().unwrap() = Some(status);
+        persist_session(&shared_data);
+    }
+}
+
+fn collect_work(
+    client: &Client,
+    work_directory: &Path,
+    target_directory: &Path,
+) -> CollectionStatus {
+    if !work_directory.is_dir() {
+        return CollectionStatus::MissingDirectory;
+    }
+    match collect_client(client, work_directory, target_directory) {
+        Ok(file_count) => CollectionStatus::Collected {
+            time: Utc::now(),
+            file_count,
+        },
+        Err(err) => CollectionStatus::Failed(err.to_string()),
+    }
+}
+
+/// Writes `<client>.tar` and `<client>.manifest.json`, returns the number of collected files.
+fn collect_client(
+    client: &Client,
+    work_directory: &Path,
+    target_directory: &Path,
+) -> io::Result<usize> {
+    fs::create_dir_all(target_directory)?;
+    let archive = File::create(target_directory.join(format!("{}.tar", client.name)))?;
+    let mut builder = tar::Builder::new(archive);
+
+    let mut files = Vec::new();
+    collect_files(work_directory, Path::new(""), &mut files)?;
+    let mut entries = Vec::new();
+    for relative_path in &files {
+        let path = work_directory.join(relative_path);
+        // read once so archive and manifest agree even if the student is still writing
+        let content = fs::read(&path)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata(&fs::metadata(&path)?);
+        header.set_size(content.len() as u64);
+        builder.append_data(
+            &mut header,
+            Path::new(&client.name).join(relative_path),
+            content.as_slice(),
+        )?;
+        entries.push(ManifestEntry {
+            path: relative_path.to_string_lossy().to_string(),
+            size: content.len() as u64,
+            sha256: format!("{:x}", Sha256::digest(&content)),
+        });
+    }
+    builder.finish()?;
+
+    let manifest = Manifest {
+        client: client.name.clone(),
+        work_directory: work_directory.to_string_lossy().to_string(),
+        collected_at: Utc::now(),
+        files: entries,
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest)?;
+    fs::write(
+        target_directory.join(format!("{}.manifest.json", client.name)),
+        manifest_json,
+    )?;
+    Ok(files.len())
+}
+
+/// Recursively lists regular files, symlinks are skipped so nothing outside the directory leaks in.
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut dir_entries = fs::read_dir(root.join(relative))?.collect::<io::Result<Vec<_>>>()?;
+    dir_entries.sort_by_key(|entry| entry.file_name());
+    for entry in dir_entries {
+        let file_type = entry.file_type()?;
+        let relative_path = relative.join(entry.file_name());
+        if file_type.is_dir() {
+            collect_files(root, &relative_path, files)?;
+        } else if file_type.is_file() {
+            files.push(relative_path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, net::Ipv4Addr};
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn client() -> Client {
+        Client::new("client-01".to_string(), Ipv4Addr::LOCALHOST)
+    }
+
+    #[test]
+    fn archives_the_work_directory_with_a_manifest() {
+        let temp_dir = TempDir::new("collection");
+        let work_directory = temp_dir.path().join("work");
+        let target_directory = temp_dir.path().join("archive");
+        fs::create_dir_all(work_directory.join("src")).unwrap();
+        fs::write(work_directory.join("notes.txt"), "notes").unwrap();
+        fs::write(work_directory.join("src/Main.java"), "class Main {}").unwrap();
+        fs::write(temp_dir.path().join("secret.txt"), "secret").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("secret.txt"),
+            work_directory.join("link.txt"),
+        )
+        .unwrap();
+
+        let status = collect_work(&client(), &work_directory, &target_directory);
+        assert!(
+            matches!(status, CollectionStatus::Collected { file_count: 2, .. }),
+            "{:?}",
+            status
+        );
+
+        let archive = File::open(target_directory.join("client-01.tar")).unwrap();
+        let mut archive = tar::Archive::new(archive);
+        let mut archived = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut content = String::new();
+                entry.read_to_string(&mut content).unwrap();
+                (entry.path().unwrap().to_string_lossy().to_string(), content)
+            })
+            .collect::<Vec<_>>();
+        archived.sort();
+        assert_eq!(
+            archived,
+            vec![
+                ("client-01/notes.txt".to_string(), "notes".to_string()),
+                (
+                    "client-01/src/Main.java".to_string(),
+                    "class Main {}".to_string()
+                ),
+            ]
+        );
+
+        let manifest: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(target_directory.join("client-01.manifest.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["client"], "client-01");
+        let files = manifest["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0]["path"], "notes.txt");
+        assert_eq!(files[0]["size"], 5);
+        assert_eq!(
+            files[0]["sha256"],
+            format!("{:x}", Sha256::digest(b"notes")).as_str()
+        );
+    }
+
+    #[test]
+    fn reports_a_missing_work_directory() {
+        let temp_dir = TempDir::new("collection_missing");
+        let target_directory = temp_dir.path().join("archive");
+        let status = collect_work(&client(), &temp_dir.path().join("work"), &target_directory);
+        assert!(matches!(status, CollectionStatus::MissingDirectory));
+        assert!(!target_directory.exists());
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::Write,
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// older entries are dropped once the log holds this many
+const EVENT_LOG_CAPACITY: usize = 200;
+/// a message that repeats within this many seconds of its last occurrence only counts up
+/// its entry, e.g. a client whose kanata keeps becoming unreachable
+const REPEAT_WINDOW_SECONDS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARN",
+            Severity::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: DateTime<Utc>,
+    pub severity: Severity,
+    /// subsystem the entry comes from, e.g. `kanata` or `webserver`
+    pub source: &'static str,
+    pub message: String,
+    /// occurrences after the first one, `time` is the one of the last occurrence
+    pub repeats: u32,
+}
+
+/// Operational events of all subsystems for the supervisor, unlike the exam record
+/// this is not evidence of the exam but helps noticing what goes wrong.
+#[derive(Debug)]
+pub struct EventLog {
+    entries: Mutex<VecDeque<LogEntry>>,
+    /// every entry is appended to this file as well
+    mirror: Mutex<Option<File>>,
+}
+
+impl EventLog {
+    pub fn new(mirror_path: Option<&str>) -> Self {
+        let event_log = EventLog {
+            entries: Mutex::new(VecDeque::with_capacity(EVENT_LOG_CAPACITY)),
+            mirror: Mutex::new(None),
+        };
+        if let Some(path) = mirror_path {
+            match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => *event_log.mirror.lock().unwrap() = Some(file),
+                Err(err) => event_log.push(
+                    Severity::Error,
+                    "event log",
+                    format!("could not open log file '{}': {}", path, err),
+                ),
+            }
+        }
+        event_log
+    }
+
+    /// Appends an entry, a repeated message moves its earlier entry to the end instead and
+    /// is not mirrored to the file again.
+    pub fn push(&self, severity: Severity, source: &'static str, message: String) {
+        let now = Utc::now();
+        let mut entries = self.entries.lock().unwrap();
+        let repeated = entries.iter().rposition(|entry| {
+            entry.severity == severity
+                && entry.source == source
+                && entry.message == message
+                && now - entry.time < Duration::seconds(REPEAT_WINDOW_SECONDS)
+        });
+        if let Some(mut entry) = repeated.and_then(|index| entries.remove(index)) {
+            entry.time = now;
+            entry.repeats += 1;
+            entries.push_back(entry);
+            return;
+        }
+        let entry = LogEntry {
+            time: now,
+            severity,
+            source,
+            message,
+            repeats: 0,
+        };
+        if let Some(file) = self.mirror.lock().unwrap().as_mut() {
+            let _ = writeln!(
+                file,
+                "{} {:5} {}: {}",
+                entry.time.to_rfc3339(),
+                entry.severity.label(),
+                entry.source,
+                entry.message
+            );
+        }
+        if entries.len() == EVENT_LOG_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// The newest `count` entries, oldest first.
+    pub fn newest(&self, count: usize) -> Vec<LogEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .skip(entries.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+
+    /// Number of entries with at least `severity` that are still in the log.
+    pub fn count_at_least(&self, severity: Severity) -> usize {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.severity >= severity)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_messages_count_up_their_entry() {
+        let event_log = EventLog::new(None);
+        for _ in 0..3 {
+            event_log.push(
+                Severity::Warning,
+                "kanata",
+                "a is not reachable".to_string(),
+            );
+            event_log.push(Severity::Info, "kanata", "a is reachable".to_string());
+        }
+        event_log.push(
+            Severity::Warning,
+            "kanata",
+            "a is not reachable".to_string(),
+        );
+        let entries = event_log.newest(EVENT_LOG_CAPACITY);
+        assert_eq!(entries.len(), 2);
+        // the last occurrence decides the order
+        assert_eq!(entries[0].message, "a is reachable");
+        assert_eq!(entries[0].repeats, 2);
+        assert_eq!(entries[1].message, "a is not reachable");
+        assert_eq!(entries[1].repeats, 3);
+    }
+
+    #[test]
+    fn same_message_of_another_source_is_kept() {
+        let event_log = EventLog::new(None);
+        event_log.push(Severity::Error, "kanata", "failed".to_string());
+        event_log.push(Severity::Error, "webserver", "failed".to_string());
+        assert_eq!(event_log.newest(EVENT_LOG_CAPACITY).len(), 2);
+        assert_eq!(event_log.count_at_least(Severity::Error), 2);
+    }
+}
@@ -0,0 +1,240 @@
+use std::{sync::Arc, thread};
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Utc};
+
+use crate::{
+    collection::start_collection,
+    exam_record::RecordCategory,
+    input_parser::TaskDescriptionTarget,
+    kanata_tcp::{enable_keyboards, try_layer_change_all, try_layer_change_client},
+    persistance::{persist_session, persist_time},
+    shared_data::SharedData,
+    symlinks::{lock_taskdescription, unlock_taskdescription},
+    time_adjustment::{apply_adjustment, Adjustment},
+};
+
+/// Starts the timer with the configured duration and unlocks the task description.
+///
+/// Returns false if the timer was already running.
+pub fn start_exam(shared_data: Arc<SharedData>) -> bool {
+    let mut times = shared_data.times.lock().unwrap();
+    // no times stored => timer has not yet been started
+    if times.is_some() {
+        return false;
+    }
+    let now = Utc::now();
+    let duration = Duration::minutes(shared_data.config.timer_duration_minutes);
+    *times = Some((now, duration));
+    drop(times);
+    shared_data.record_event(
+        RecordCategory::Timer,
+        format!("exam started with {}min", duration.num_minutes()),
+    );
+    unlock_taskdescription(shared_data.clone());
+    persist_time(now, duration);
+    catch_up_phase(shared_data);
+    true
+}
+
+/// Enters the phase at `index` of `config.phases` and runs its actions.
+pub fn enter_phase(shared_data: Arc<SharedData>, index: usize) {
+    let Some(phase) = shared_data.config.phases.get(index) else {
+        return;
+    };
+    *shared_data.phase.lock().unwrap() = index;
+    *shared_data.phase_started.lock().unwrap() = Some(Utc::now());
+    shared_data.record_event(
+        RecordCategory::Phase,
+        format!("phase '{}' entered", phase.name),
+    );
+    persist_session(&shared_data);
+
+    if phase.start_timer {
+        start_exam(shared_data.clone());
+    }
+    run_phase_actions(shared_data, index);
+}
+
+/// Switches task description and keyboards and collects the work as the phase at `index`
+/// demands, starting the timer is left to the caller.
+fn run_phase_actions(shared_data: Arc<SharedData>, index: usize) {
+    let Some(phase) = shared_data.config.phases.get(index) else {
+        return;
+    };
+    if let Some(target) = phase.task_description {
+        let target_path = match target {
+            TaskDescriptionTarget::Dummy => &shared_data.symlink_info.dummy_target,
+            TaskDescriptionTarget::Real => &shared_data.symlink_info.real_target,
+        };
+        // starting the timer may already have unlocked it
+        let already_set = shared_data.symlink_target.lock().unwrap().as_ref() == Some(target_path);
+        if !already_set {
+            match target {
+                TaskDescriptionTarget::Dummy => lock_taskdescription(shared_data.clone()),
+                TaskDescriptionTarget::Real => unlock_taskdescription(shared_data.clone()),
+            }
+        }
+    }
+    if let Some(layer) = &phase.keyboard_layer {
+        try_layer_change_all(shared_data.clone(), layer);
+    }
+    if phase.collect_work {
+        start_collection(shared_data.clone());
+    }
+}
+
+/// Enters the phase after the current one, if there is any.
+pub fn advance_phase(shared_data: Arc<SharedData>) {
+    let next = *shared_data.phase.lock().unwrap() + 1;
+    enter_phase(shared_data, next);
+}
+
+/// A timer started outside of the phases (Enter, scheduled start) skips ahead to the
+/// first phase that starts the timer and runs its actions, e.g. enables the keyboards.
+fn catch_up_phase(shared_data: Arc<SharedData>) {
+    let Some(timer_phase) = shared_data
+        .config
+        .phases
+        .iter()
+        .position(|phase| phase.start_timer)
+    else {
+        return;
+    };
+    let mut phase = shared_data.phase.lock().unwrap();
+    if *phase >= timer_phase {
+        return;
+    }
+    *phase = timer_phase;
+    drop(phase);
+    *shared_data.phase_started.lock().unwrap() = Some(Utc::now());
+    shared_data.record_event(
+        RecordCategory::Phase,
+        format!(
+            "phase '{}' entered with the timer",
+            shared_data.config.phases[timer_phase].name
+        ),
+    );
+    persist_session(&shared_data);
+    run_phase_actions(shared_data, timer_phase);
+}
+
+/// Applies `adjustment` to the running timer, see [`apply_adjustment`], as long as every
+/// group keeps some time.
+pub fn adjust_duration(shared_data: &SharedData, adjustment: Adjustment) -> Result<(), String> {
+    let mut times = shared_data.times.lock().unwrap();
+    let Some((start_time, duration)) = *times else {
+        return Err("the timer is not running".to_string());
+    };
+    let new_duration = apply_adjustment(start_time, duration, adjustment)?;
+    shared_data.check_group_durations(start_time, new_duration)?;
+    *times = Some((start_time, new_duration));
+    drop(times);
+    shared_data.record_event(
+        RecordCategory::Timer,
+        format!("duration changed to {}min", new_duration.num_minutes()),
+    );
+    persist_time(start_time, new_duration);
+    Ok(())
+}
+
+/// Parses a wall-clock time like `09:30` as that time of today.
+pub fn parse_time_of_day(text: &str) -> Result<DateTime<Utc>, String> {
+    let time = NaiveTime::parse_from_str(text.trim(), "%H:%M")
+        .map_err(|_| format!("'{}' is not a time like 09:30", text.trim()))?;
+    Local
+        .from_local_datetime(&Local::now().date_naive().and_time(time))
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or(format!("{} does not exist today", text.trim()))
+}
+
+/// Parses a wall-clock time like `09:30` as the next start of today.
+pub fn parse_start_time(text: &str) -> Result<DateTime<Utc>, String> {
+    let start = parse_time_of_day(text)?;
+    if start <= Utc::now() {
+        return Err(format!("{} has already passed", text.trim()));
+    }
+    Ok(start)
+}
+
+/// Plans the exam start, `None` cancels a planned start.
+pub fn schedule_start(shared_data: &SharedData, start: Option<DateTime<Utc>>) {
+    *shared_data.scheduled_start.lock().unwrap() = start;
+    let description = match start {
+        Some(start) => format!(
+            "exam start scheduled for {}",
+            start.with_timezone(&Local).format("%H:%M")
+        ),
+        None => "scheduled exam start cancelled".to_string(),
+    };
+    shared_data.record_event(RecordCategory::Timer, description);
+    persist_session(shared_data);
+}
+
+/// Starts the exam once the scheduled start time is reached, leaves phases that end
+/// with the timer or their own countdown and locks keyboards whose time is up.
+pub fn start_scheduler_thread(shared_data: Arc<SharedData>) {
+    thread::spawn(move || {
+        let mut last_check = Utc::now();
+        loop {
+            let now = Utc::now();
+            if shared_data.config.kanata_auto_lock_when_time_is_up {
+                auto_lock_keyboards(&shared_data, last_check, now);
+            }
+            last_check = now;
+            check_schedule(shared_data.clone());
+            thread::sleep(std::time::Duration::from_secs(1));
+        }
+    });
+}
+
+/// Disables the keyboards of all clients whose time ran out since `last_check`.
+fn auto_lock_keyboards(
+    shared_data: &Arc<SharedData>,
+    last_check: DateTime<Utc>,
+    now: DateTime<Utc>,
+) {
+    for client in &shared_data.clients {
+        let finish_time = shared_data.finish_time_of_client(client);
+        let finished_early = client.finished.lock().unwrap().is_some();
+        if !finished_early
+            && finish_time.is_some_and(|finish_time| last_check < finish_time && finish_time <= now)
+        {
+            try_layer_change_client(shared_data, client, "disabled");
+        }
+    }
+}
+
+/// Starts the exam at the scheduled time and leaves phases that are over.
+fn check_schedule(shared_data: Arc<SharedData>) {
+    let scheduled_start = *shared_data.scheduled_start.lock().unwrap();
+    if scheduled_start.is_some_and(|start| Utc::now() >= start) {
+        *shared_data.scheduled_start.lock().unwrap() = None;
+        persist_session(&shared_data);
+        if start_exam(shared_data.clone()) {
+            shared_data.record_event(
+                RecordCategory::Timer,
+                "exam started at the scheduled time".to_string(),
+            );
+            // the phase entered with the timer already switched the keyboards
+            let phase_sets_layer = shared_data
+                .current_phase()
+                .is_some_and(|phase| phase.keyboard_layer.is_some());
+            if !phase_sets_layer {
+                enable_keyboards(shared_data.clone());
+            }
+        }
+    }
+    let time_is_up = shared_data
+        .latest_finish_time()
+        .is_some_and(|finish_time| Utc::now() >= finish_time);
+    let advance = shared_data
+        .current_phase()
+        .is_some_and(|phase| phase.advance_when_time_is_up);
+    let phase_over = shared_data
+        .phase_end()
+        .is_some_and(|phase_end| Utc::now() >= phase_end);
+    if (time_is_up && advance) || phase_over {
+        advance_phase(shared_data.clone());
+    }
+}
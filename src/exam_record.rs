@@ -0,0 +1,178 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordCategory {
+    Timer,
+    Keyboards,
+    TaskDescription,
+    Collection,
+    Submission,
+    Note,
+    Student,
+    Phase,
+    Undo,
+}
+
+/// One event of the exam, chained to its predecessor by hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordEntry {
+    pub time: DateTime<Utc>,
+    pub category: RecordCategory,
+    pub description: String,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+/// Append-only log of everything that happened during the exam.
+///
+/// Every entry contains the hash of the previous one, so editing or removing an entry later
+/// breaks the chain and is detected by `verify`. The chain is not keyed, anyone able to edit
+/// the file can rebuild it, so it is only evidence together with a head hash kept elsewhere,
+/// like the one shown in the TUI and written to every export.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ExamRecord {
+    pub entries: Vec<RecordEntry>,
+}
+
+impl ExamRecord {
+    pub fn append(&mut self, category: RecordCategory, description: String) {
+        let time = Utc::now();
+        let previous_hash = self.head_hash().to_string();
+        let hash = entry_hash(&previous_hash, time, category, &description);
+        self.entries.push(RecordEntry {
+            time,
+            category,
+            description,
+            previous_hash,
+            hash,
+        });
+    }
+
+    /// Hash of the newest entry, which transitively covers the whole record.
+    pub fn head_hash(&self) -> &str {
+        self.entries
+            .last()
+            .map(|entry| entry.hash.as_str())
+            .unwrap_or(GENESIS_HASH)
+    }
+
+    pub fn verify(&self) -> bool {
+        let mut previous_hash = GENESIS_HASH;
+        for entry in &self.entries {
+            let expected = entry_hash(
+                previous_hash,
+                entry.time,
+                entry.category,
+                &entry.description,
+            );
+            if entry.previous_hash != previous_hash || entry.hash != expected {
+                return false;
+            }
+            previous_hash = &entry.hash;
+        }
+        true
+    }
+}
+
+fn entry_hash(
+    previous_hash: &str,
+    time: DateTime<Utc>,
+    category: RecordCategory,
+    description: &str,
+) -> String {
+    let mut hasher = Sha256::new();
+    let category = serde_json::to_string(&category).unwrap_or_default();
+    for field in [previous_hash, &time.to_rfc3339(), &category, description] {
+        hasher.update(field.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_record() -> ExamRecord {
+        let mut record = ExamRecord::default();
+        record.append(RecordCategory::Timer, "exam started with 90min".to_string());
+        record.append(
+            RecordCategory::Keyboards,
+            "all keyboards enabled".to_string(),
+        );
+        record.append(RecordCategory::Note, "a001-01: toilet break".to_string());
+        record
+    }
+
+    #[test]
+    fn chains_the_entries() {
+        let record = sample_record();
+        assert!(record.verify());
+        assert!(ExamRecord::default().verify());
+        assert_eq!(ExamRecord::default().head_hash(), GENESIS_HASH);
+        assert_eq!(record.entries[0].previous_hash, GENESIS_HASH);
+        assert_eq!(record.entries[1].previous_hash, record.entries[0].hash);
+        assert_eq!(record.head_hash(), record.entries[2].hash);
+    }
+
+    #[test]
+    fn survives_serialization() {
+        let record = sample_record();
+        let json = serde_json::to_string(&record).unwrap();
+        let restored = serde_json::from_str::<ExamRecord>(&json).unwrap();
+        assert!(restored.verify());
+        assert_eq!(restored.head_hash(), record.head_hash());
+    }
+
+    #[test]
+    fn detects_edited_entries() {
+        let mut record = sample_record();
+        record.entries[1].description = "all keyboards disabled".to_string();
+        assert!(!record.verify());
+
+        let mut record = sample_record();
+        record.entries[0].time += chrono::Duration::seconds(1);
+        assert!(!record.verify());
+
+        let mut record = sample_record();
+        record.entries[2].category = RecordCategory::Timer;
+        assert!(!record.verify());
+    }
+
+    #[test]
+    fn detects_removed_and_reordered_entries() {
+        let mut record = sample_record();
+        record.entries.remove(1);
+        assert!(!record.verify());
+
+        let mut record = sample_record();
+        record.entries.swap(0, 1);
+        assert!(!record.verify());
+
+        // dropping the newest entries keeps the chain valid, only the head hash reveals it
+        let mut record = sample_record();
+        let head_hash = record.head_hash().to_string();
+        record.entries.pop();
+        assert!(record.verify());
+        assert_ne!(record.head_hash(), head_hash);
+    }
+
+    #[test]
+    fn detects_rehashed_entries_without_their_successors() {
+        let mut record = sample_record();
+        let entry = &mut record.entries[1];
+        entry.description = "all keyboards disabled".to_string();
+        entry.hash = entry_hash(
+            &entry.previous_hash,
+            entry.time,
+            entry.category,
+            &entry.description,
+        );
+        assert!(!record.verify());
+    }
+}
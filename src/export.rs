@@ -0,0 +1,414 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    client::Note, collection::CollectionStatus, exam_record::RecordEntry, roster::Student,
+    shared_data::SharedData,
+};
+
+#[derive(Debug, Serialize)]
+struct ExamReport {
+    room: String,
+    exported_at: DateTime<Utc>,
+    start_time: Option<DateTime<Utc>>,
+    finish_time: Option<DateTime<Utc>>,
+    duration_minutes: Option<i64>,
+    clients: Vec<ClientReport>,
+    unauthorized_accesses: Vec<UnauthorizedAccessReport>,
+    events: Vec<RecordEntry>,
+    /// hash of the newest event, covering all events before it
+    record_head_hash: String,
+    record_chain_valid: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ClientReport {
+    name: String,
+    student: Option<Student>,
+    ip_address: String,
+    /// only set for clients that started individually
+    start_override: Option<DateTime<Utc>>,
+    finish_time: Option<DateTime<Utc>>,
+    finished_early: Option<DateTime<Utc>>,
+    group: Option<String>,
+    keyboard_layer: Option<String>,
+    last_timer_access: Option<DateTime<Utc>>,
+    last_heartbeat: Option<DateTime<Utc>>,
+    submissions: Vec<SubmissionReport>,
+    collection: Option<String>,
+    notes: Vec<Note>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubmissionReport {
+    file_name: String,
+    time: DateTime<Utc>,
+    size: usize,
+    sha256: String,
+}
+
+#[derive(Debug, Serialize)]
+struct UnauthorizedAccessReport {
+    ip_address: String,
+    first_access: DateTime<Utc>,
+    last_access: DateTime<Utc>,
+    hit_count: u32,
+    user_agent: Option<String>,
+    last_path: String,
+}
+
+/// Writes `exam_<room>_<timestamp>.json`, a Markdown version of it and a `.sha256` file
+/// covering both, returns the path of the JSON report.
+pub fn export_exam_record(shared_data: &SharedData) -> io::Result<PathBuf> {
+    let report = build_report(shared_data);
+    let directory = Path::new(&shared_data.config.export_directory);
+    fs::create_dir_all(directory)?;
+    let timestamp = report
+        .exported_at
+        .with_timezone(&Local)
+        .format("%Y%m%d-%H%M%S");
+    let json = serde_json::to_string_pretty(&report)?;
+    let (base_name, mut json_file) =
+        create_json_file(directory, &format!("exam_{}_{}", report.room, timestamp))?;
+    let json_name = format!("{}.json", base_name);
+    json_file.write_all(json.as_bytes())?;
+
+    let markdown_name = format!("{}.md", base_name);
+    let markdown = render_markdown(&report);
+    fs::write(directory.join(&markdown_name), &markdown)?;
+
+    // same format as sha256sum, so the export can be checked with `sha256sum -c`, which
+    // skips the comment; the head hash is what the supervisor compares with the TUI
+    let checksums = format!(
+        "# exam record head hash {} ({} events)\n{:x}  {}\n{:x}  {}\n",
+        report.record_head_hash,
+        report.events.len(),
+        Sha256::digest(json.as_bytes()),
+        json_name,
+        Sha256::digest(markdown.as_bytes()),
+        markdown_name
+    );
+    fs::write(directory.join(format!("{}.sha256", base_name)), checksums)?;
+
+    Ok(directory.join(json_name))
+}
+
+/// Creates `<base_name>.json`, adding a counter to the name if an export of the same second
+/// already exists, returns the name without extension and the file.
+fn create_json_file(directory: &Path, base_name: &str) -> io::Result<(String, File)> {
+    let mut counter = 1;
+    loop {
+        let name = match counter {
+            1 => base_name.to_string(),
+            counter => format!("{}-{}", base_name, counter),
+        };
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(directory.join(format!("{}.json", name)))
+        {
+            Ok(json_file) => return Ok((name, json_file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => counter += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn build_report(shared_data: &SharedData) -> ExamReport {
+    let times = *shared_data.times.lock().unwrap();
+    let record = shared_data.record.lock().unwrap().clone();
+    let clients = shared_data
+        .clients
+        .iter()
+        .map(|client| {
+            // locks the client's fields itself, so not inside the literal below whose
+            // temporary guards live until its end
+            let finish_time = shared_data.finish_time_of_client(client);
+            ClientReport {
+                name: client.name.clone(),
+                student: client.student.lock().unwrap().clone(),
+                ip_address: client.ip_address.to_string(),
+                start_override: *client.start_override.lock().unwrap(),
+                finish_time,
+                finished_early: *client.finished.lock().unwrap(),
+                group: client.group.lock().unwrap().clone(),
+                keyboard_layer: client.current_layer.lock().unwrap().clone(),
+                last_timer_access: *client.last_timer_access.lock().unwrap(),
+                last_heartbeat: *client.last_heartbeat.lock().unwrap(),
+                submissions: client
+                    .submissions
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|submission| SubmissionReport {
+                        file_name: submission.file_name.clone(),
+                        time: submission.time,
+                        size: submission.size,
+                        sha256: submission.sha256.clone(),
+                    })
+                    .collect(),
+                collection: client
+                    .collection_status
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(describe_collection_status),
+                notes: client.notes.lock().unwrap().clone(),
+            }
+        })
+        .collect();
+    let unauthorized_accesses = shared_data
+        .nonclients
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|nonclient| UnauthorizedAccessReport {
+            ip_address: nonclient.ip_address.to_string(),
+            first_access: nonclient.first_timer_access,
+            last_access: nonclient.last_timer_access,
+            hit_count: nonclient.hit_count,
+            user_agent: nonclient.user_agent.clone(),
+            last_path: nonclient.last_path.clone(),
+        })
+        .collect();
+
+    ExamReport {
+        room: shared_data.room.clone(),
+        exported_at: Utc::now(),
+        start_time: times.map(|(start_time, _)| start_time),
+        finish_time: times.map(|(start_time, duration)| start_time + duration),
+        duration_minutes: times.map(|(_, duration)| duration.num_minutes()),
+        clients,
+        unauthorized_accesses,
+        record_head_hash: record.head_hash().to_string(),
+        record_chain_valid: record.verify(),
+        events: record.entries,
+    }
+}
+
+fn describe_collection_status(status: &CollectionStatus) -> String {
+    match status {
+        CollectionStatus::Running => "running".to_string(),
+        CollectionStatus::Collected { time, file_count } => {
+            format!("{} files at {}", file_count, time.to_rfc3339())
+        }
+        CollectionStatus::MissingDirectory => "work directory missing".to_string(),
+        CollectionStatus::Failed(err) => format!("failed: {}", err),
+    }
+}
+
+fn format_time(time: Option<DateTime<Utc>>) -> String {
+    time.map(|time| {
+        time.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    })
+    .unwrap_or("-".to_string())
+}
+
+/// Escapes free text so it can neither break a table row nor start a code span or a new line.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '|' | '`' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\r' | '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Wraps text in a code span whose fence is longer than any backtick run inside it.
+fn code_span(text: &str) -> String {
+    let text = text.replace(['\r', '\n'], " ");
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run + 1);
+    // a span starting or ending with a backtick needs padding, which the renderer strips
+    let padding = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{fence}{padding}{text}{padding}{fence}")
+}
+
+fn render_markdown(report: &ExamReport) -> String {
+    let mut md = String::new();
+    md.push_str(&format!(
+        "# Exam record of room {}\n\n",
+        escape(&report.room)
+    ));
+    md.push_str(&format!(
+        "- Exported: {}\n",
+        format_time(Some(report.exported_at))
+    ));
+    md.push_str(&format!("- Start: {}\n", format_time(report.start_time)));
+    md.push_str(&format!("- Finish: {}\n", format_time(report.finish_time)));
+    md.push_str(&format!(
+        "- Duration: {}\n",
+        report
+            .duration_minutes
+            .map(|minutes| format!("{} min", minutes))
+            .unwrap_or("-".to_string())
+    ));
+    md.push_str(&format!(
+        "- Record head hash: `{}` ({})\n\n",
+        report.record_head_hash,
+        if report.record_chain_valid {
+            "chain valid"
+        } else {
+            "CHAIN BROKEN"
+        }
+    ));
+
+    md.push_str("## Clients\n\n");
+    md.push_str("| Name | Student | IP address | Finish | Keyboard layer | Last timer access | Last heartbeat | Submissions | Collection | Notes |\n");
+    md.push_str("|---|---|---|---|---|---|---|---|---|---|\n");
+    for client in &report.clients {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+            escape(&client.name),
+            client
+                .student
+                .as_ref()
+                .map(|student| escape(&student.display()))
+                .unwrap_or("-".to_string()),
+            client.ip_address,
+            match client.finished_early {
+                Some(finished) => format!("handed in {}", format_time(Some(finished))),
+                None => format_time(client.finish_time),
+            },
+            escape(client.keyboard_layer.as_deref().unwrap_or("-")),
+            format_time(client.last_timer_access),
+            format_time(client.last_heartbeat),
+            client.submissions.len(),
+            escape(client.collection.as_deref().unwrap_or("-")),
+            client.notes.len(),
+        ));
+    }
+
+    let submissions = report
+        .clients
+        .iter()
+        .flat_map(|client| {
+            client
+                .submissions
+                .iter()
+                .map(move |submission| (client, submission))
+        })
+        .collect::<Vec<_>>();
+    if !submissions.is_empty() {
+        md.push_str("\n## Submissions\n\n");
+        for (client, submission) in submissions {
+            md.push_str(&format!(
+                "- {} {}: {} ({} bytes, SHA-256 `{}`)\n",
+                format_time(Some(submission.time)),
+                escape(&client.name),
+                code_span(&submission.file_name),
+                submission.size,
+                submission.sha256
+            ));
+        }
+    }
+
+    let notes = report
+        .clients
+        .iter()
+        .flat_map(|client| client.notes.iter().map(move |note| (client, note)))
+        .collect::<Vec<_>>();
+    if !notes.is_empty() {
+        md.push_str("\n## Notes\n\n");
+        for (client, note) in notes {
+            md.push_str(&format!(
+                "- {} {}{}: {}\n",
+                format_time(Some(note.time)),
+                escape(&client.name),
+                client
+                    .student
+                    .as_ref()
+                    .map(|student| format!(" ({})", escape(&student.display())))
+                    .unwrap_or_default(),
+                escape(&note.text)
+            ));
+        }
+    }
+
+    md.push_str("\n## Unauthorized timer accesses\n\n");
+    if report.unauthorized_accesses.is_empty() {
+        md.push_str("None.\n");
+    }
+    for access in &report.unauthorized_accesses {
+        md.push_str(&format!(
+            "- {}: {} hits between {} and {}, last path {}, user agent {}\n",
+            access.ip_address,
+            access.hit_count,
+            format_time(Some(access.first_access)),
+            format_time(Some(access.last_access)),
+            code_span(&access.last_path),
+            code_span(access.user_agent.as_deref().unwrap_or("unknown")),
+        ));
+    }
+
+    md.push_str("\n## Events\n\n");
+    for entry in &report.events {
+        md.push_str(&format!(
+            "- {} [{}] {} (`{}`)\n",
+            format_time(Some(entry.time)),
+            serde_json::to_string(&entry.category)
+                .unwrap_or_default()
+                .trim_matches('"'),
+            escape(&entry.description),
+            // a tampered record may hold anything as hash
+            entry.hash.get(..12).unwrap_or(&entry.hash)
+        ));
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn escapes_table_breaking_text() {
+        assert_eq!(escape("a | b"), "a \\| b");
+        assert_eq!(escape("`x`\\"), "\\`x\\`\\\\");
+        assert_eq!(escape("line one\nline two\r\n"), "line one line two  ");
+        assert_eq!(escape("plain_name"), "plain_name");
+    }
+
+    #[test]
+    fn code_spans_outlast_inner_backticks() {
+        assert_eq!(code_span("main.rs"), "`main.rs`");
+        assert_eq!(code_span("a``b"), "```a``b```");
+        assert_eq!(code_span("`x"), "`` `x ``");
+        assert_eq!(code_span("two\nlines"), "`two lines`");
+    }
+
+    #[test]
+    fn exports_of_the_same_second_get_a_counter() {
+        let directory = TempDir::new("export_names");
+        let (first, _) = create_json_file(directory.path(), "exam_r1_20260101-120000").unwrap();
+        let (second, _) = create_json_file(directory.path(), "exam_r1_20260101-120000").unwrap();
+        let (third, _) = create_json_file(directory.path(), "exam_r1_20260101-120000").unwrap();
+        assert_eq!(first, "exam_r1_20260101-120000");
+        assert_eq!(second, "exam_r1_20260101-120000-2");
+        assert_eq!(third, "exam_r1_20260101-120000-3");
+    }
+}
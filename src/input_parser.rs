@@ -1,12 +1,17 @@
-use serde::Deserialize;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fs::{self, read_to_string},
     io::Write,
     net::{Ipv4Addr, ToSocketAddrs},
     path::Path,
 };
 
-use crate::client::Client;
+use crate::{
+    client::Client,
+    tui_action::{Action, ConfirmationMode},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
@@ -15,13 +20,143 @@ pub struct Config {
     pub timer_webpage_refresh_seconds: u32,
     pub timer_webpage_refresh_unstarted_seconds: u32,
     pub timer_allow_nonclient_access: bool,
+    /// has to be at least 1, the config is rejected otherwise
+    #[serde(default = "default_timer_heartbeat_interval_seconds")]
+    pub timer_heartbeat_interval_seconds: u32,
+    /// addresses of supervisor laptops, treated like clients for the timer page and files
+    #[serde(default)]
+    pub timer_nonclient_allowlist: Vec<Ipv4Addr>,
+    /// addresses of known infrastructure, neither tracked nor alerted
+    #[serde(default)]
+    pub timer_nonclient_ignorelist: Vec<Ipv4Addr>,
     pub kanata_client_scan_interval_seconds: u64,
     pub kanata_tcp_timeout_ms: u64,
     pub kanata_port: u16,
+    /// keyboards are disabled client by client once their individual time is up
+    #[serde(default)]
+    pub kanata_auto_lock_when_time_is_up: bool,
     pub tui_show_nonclient_timer_accesses: bool,
+    /// clients without heartbeat for longer than this are flagged in the client overview
+    #[serde(default = "default_tui_heartbeat_timeout_seconds")]
+    pub tui_heartbeat_timeout_seconds: i64,
+    /// room wide actions that have to be confirmed, all others are executed right away
+    #[serde(default)]
+    pub tui_confirmations: HashMap<Action, ConfirmationMode>,
+    /// time for the second press of `double_press` and how long `hold` has to be held
+    #[serde(default = "default_tui_confirmation_ms")]
+    pub tui_confirmation_ms: u64,
+    /// keys of TUI actions that differ from the defaults, e.g. `"quit": "Q"`
+    #[serde(default)]
+    pub keybindings: HashMap<Action, String>,
+    #[serde(default)]
+    pub timer_warning_milestones: Vec<Milestone>,
+    #[serde(default)]
+    pub timer_files_directory: Option<String>,
+    #[serde(default)]
+    pub timer_files_availability: FilesAvailability,
+    /// uploads through `/submit` are stored here, `None` disables the endpoint
+    #[serde(default)]
+    pub submission_directory: Option<String>,
+    #[serde(default = "default_submission_grace_minutes")]
+    pub submission_grace_minutes: i64,
+    #[serde(default = "default_submission_max_size_mb")]
+    pub submission_max_size_mb: u64,
+    /// CSV with matriculation number and name of the participating students
+    #[serde(default)]
+    pub roster_path: Option<String>,
+    /// students can assign themselves to their seat on `/checkin`
+    #[serde(default)]
+    pub timer_checkin_enabled: bool,
+    /// steps of the exam flow in order, the first one is active after startup,
+    /// without phases the exam is run with the single keys only
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+    /// entries of the event log panel are mirrored to this file
+    #[serde(default)]
+    pub event_log_file: Option<String>,
+    /// exam records are exported to this directory
+    #[serde(default = "default_export_directory")]
+    pub export_directory: String,
     rooms: Vec<Room>,
 }
 
+fn default_timer_heartbeat_interval_seconds() -> u32 {
+    10
+}
+
+fn default_tui_heartbeat_timeout_seconds() -> i64 {
+    30
+}
+
+fn default_tui_confirmation_ms() -> u64 {
+    2000
+}
+
+fn default_submission_grace_minutes() -> i64 {
+    5
+}
+
+fn default_submission_max_size_mb() -> u64 {
+    20
+}
+
+fn default_export_directory() -> String {
+    ".".to_string()
+}
+
+/// A point in time before the end of the exam at which students and supervisor are warned.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Milestone {
+    pub minutes_left: i64,
+    /// any color understood by both CSS and ratatui, e.g. `#ffa500` or `red`
+    pub color: String,
+    pub banner: String,
+    #[serde(default)]
+    pub play_sound: bool,
+}
+
+/// A step of the exam flow, entering it triggers the configured actions.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Phase {
+    pub name: String,
+    #[serde(default)]
+    pub task_description: Option<TaskDescriptionTarget>,
+    /// kanata layer all keyboards are switched to
+    #[serde(default)]
+    pub keyboard_layer: Option<String>,
+    /// shown on the countdown page while the phase is active
+    #[serde(default)]
+    pub webpage_text: Option<String>,
+    #[serde(default)]
+    pub start_timer: bool,
+    #[serde(default)]
+    pub collect_work: bool,
+    /// the next phase is entered automatically once the timer ran out
+    #[serde(default)]
+    pub advance_when_time_is_up: bool,
+    /// the phase has its own countdown and the next phase is entered when it ends,
+    /// e.g. reading time before writing
+    #[serde(default)]
+    pub duration_minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskDescriptionTarget {
+    Dummy,
+    Real,
+}
+
+/// When the files of `timer_files_directory` are served under `/files/`.
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FilesAvailability {
+    Always,
+    #[default]
+    AfterStart,
+    WhileRunning,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Room {
     name: String,
@@ -29,6 +164,41 @@ pub struct Room {
     symlink_info: SymlinkInfo,
     client_hostnames: Vec<String>,
     control_client: String,
+    #[serde(default)]
+    collection_info: Option<CollectionInfo>,
+    #[serde(default)]
+    layout: Option<RoomLayout>,
+    #[serde(default)]
+    groups: Vec<ExamGroup>,
+}
+
+/// Students of one module that share a duration different from other modules in the room.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ExamGroup {
+    pub name: String,
+    pub duration_minutes: i64,
+    /// hostnames seated in this group initially, can be changed in the TUI
+    #[serde(default)]
+    pub clients: Vec<String>,
+}
+
+/// Physical arrangement of the clients in the room, used for the seat map.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RoomLayout {
+    pub rows: u16,
+    pub columns: u16,
+    /// `[row, column]` of each hostname, counted from 0 at the top left of the seat map
+    pub seats: HashMap<String, (u16, u16)>,
+}
+
+/// Where the work of the students is collected from at the end of the exam.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CollectionInfo {
+    /// `{client}` is replaced by the hostname of each client
+    pub work_directory: String,
+    pub archive_directory: String,
+    #[serde(default)]
+    pub collect_when_time_is_up: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -36,6 +206,19 @@ pub struct SymlinkInfo {
     pub symlink_path: String,
     pub dummy_target: String,
     pub real_target: String,
+    #[serde(default)]
+    pub mode: TaskDescriptionMode,
+}
+
+/// How the task description reaches the clients.
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskDescriptionMode {
+    /// `symlink_path` on a shared filesystem is switched between dummy and real target
+    #[default]
+    Symlink,
+    /// the timer webserver serves the current target under `/task/`, the symlink is not touched
+    Http,
 }
 
 pub fn get_rooms(config: &Config) -> Vec<&str> {
@@ -90,9 +273,51 @@ pub fn get_symlink_info_of_room(room_name: &str, config: &Config) -> Option<Syml
     }
 }
 
+/// Returns the milestone with the smallest `minutes_left` that has already been reached,
+/// none once the time is up.
+pub fn get_active_milestone(config: &Config, time_left: Duration) -> Option<&Milestone> {
+    if time_left <= Duration::zero() {
+        return None;
+    }
+    config
+        .timer_warning_milestones
+        .iter()
+        .filter(|milestone| time_left <= Duration::minutes(milestone.minutes_left))
+        .min_by_key(|milestone| milestone.minutes_left)
+}
+
+pub fn get_collection_info_of_room(room_name: &str, config: &Config) -> Option<CollectionInfo> {
+    config
+        .rooms
+        .iter()
+        .find(|room| room.name == room_name)
+        .and_then(|room| room.collection_info.clone())
+}
+
+pub fn get_layout_of_room(room_name: &str, config: &Config) -> Option<RoomLayout> {
+    config
+        .rooms
+        .iter()
+        .find(|room| room.name == room_name)
+        .and_then(|room| room.layout.clone())
+}
+
+pub fn get_groups_of_room(room_name: &str, config: &Config) -> Vec<ExamGroup> {
+    config
+        .rooms
+        .iter()
+        .find(|room| room.name == room_name)
+        .map(|room| room.groups.clone())
+        .unwrap_or_default()
+}
+
 pub fn parse_config(config_path: &str) -> Option<Config> {
     let filecontent = read_to_string(config_path).ok()?;
     let config: Config = serde_json::from_str(&filecontent).ok()?;
+    // the countdown page would send its heartbeats without pause
+    if config.timer_heartbeat_interval_seconds == 0 {
+        return None;
+    }
     for room in &config.rooms {
         if !room.client_hostnames.contains(&room.control_client) {
             return None;
@@ -107,7 +332,7 @@ fn resolve_ipv4_addr(hostname: &str) -> Option<Ipv4Addr> {
 
     for addr in addresses {
         if let std::net::SocketAddr::V4(socket_addr) = addr {
-            return Some(socket_addr.ip().clone());
+            return Some(*socket_addr.ip());
         }
     }
     None
@@ -122,3 +347,59 @@ pub fn create_default_config_if_necessary(path: &str, default_config_content: &s
     }
     Some(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn config_with_milestones() -> Config {
+        let mut config: Config =
+            serde_json::from_str(include_str!("../res/ppmngr_cfg_default.json")).unwrap();
+        config.timer_warning_milestones = [10, 1]
+            .into_iter()
+            .map(|minutes_left| Milestone {
+                minutes_left,
+                color: "red".to_string(),
+                banner: format!("{} minutes left", minutes_left),
+                play_sound: false,
+            })
+            .collect();
+        config
+    }
+
+    #[test]
+    fn closest_reached_milestone_is_active() {
+        let config = config_with_milestones();
+        let active = |time_left| {
+            get_active_milestone(&config, time_left).map(|milestone| milestone.minutes_left)
+        };
+        assert_eq!(active(Duration::minutes(11)), None);
+        assert_eq!(active(Duration::minutes(10)), Some(10));
+        assert_eq!(active(Duration::minutes(2)), Some(10));
+        assert_eq!(active(Duration::minutes(1)), Some(1));
+        assert_eq!(active(Duration::seconds(1)), Some(1));
+    }
+
+    #[test]
+    fn no_milestone_once_the_time_is_up() {
+        let config = config_with_milestones();
+        assert!(get_active_milestone(&config, Duration::zero()).is_none());
+        assert!(get_active_milestone(&config, Duration::seconds(-1)).is_none());
+        assert!(get_active_milestone(&config, Duration::minutes(-30)).is_none());
+    }
+
+    #[test]
+    fn rejects_a_heartbeat_interval_of_zero() {
+        let directory = TempDir::new("config_heartbeat");
+        let path = directory.path().join("config.json");
+        let path = path.to_str().unwrap();
+        let mut config: serde_json::Value =
+            serde_json::from_str(include_str!("../res/ppmngr_cfg_default.json")).unwrap();
+        fs::write(path, config.to_string()).unwrap();
+        assert!(parse_config(path).is_some());
+        config["timer_heartbeat_interval_seconds"] = 0.into();
+        fs::write(path, config.to_string()).unwrap();
+        assert!(parse_config(path).is_none());
+    }
+}
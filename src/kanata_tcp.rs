@@ -1,37 +1,94 @@
 use std::{
-    io::{Read, Write},
+    io::{self, Read, Write},
     net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpStream},
     sync::Arc,
     thread,
     time::Duration,
 };
 
+use chrono::Utc;
 use serde_json::Value;
 
-use crate::{client::Client, shared_data::SharedData};
+use crate::{
+    client::{Client, LayerCorrection},
+    event_log::Severity,
+    exam_record::RecordCategory,
+    persistance::persist_session,
+    shared_data::SharedData,
+};
+
+/// Retries of a drift correction back off up to 2^6 scan intervals.
+const MAX_BACKOFF_DOUBLINGS: u32 = 6;
 
-fn try_layer_change(ip: Ipv4Addr, port: u16, layer: &str, timeout: Duration) {
+fn try_layer_change(ip: Ipv4Addr, port: u16, layer: &str, timeout: Duration) -> io::Result<()> {
     let msg = format!("{{\"ChangeLayer\":{{\"new\":\"{}\"}}}}\n", layer);
 
-    match TcpStream::connect_timeout(&SocketAddr::V4(SocketAddrV4::new(ip, port)), timeout) {
-        Ok(mut stream) => {
-            let mut buf = [0; 1024];
-            // read message from kanata first, otherwise it won't accept the command
-            let _ = stream.read(&mut buf);
-            let _ = stream.write(msg.as_bytes());
-        }
-        _ => {}
-    }
+    let mut stream =
+        TcpStream::connect_timeout(&SocketAddr::V4(SocketAddrV4::new(ip, port)), timeout)?;
+    let mut buf = [0; 1024];
+    // read message from kanata first, otherwise it won't accept the command
+    let _ = stream.read(&mut buf);
+    stream.write_all(msg.as_bytes())
 }
 
-fn try_layer_change_all(shared_data: Arc<SharedData>, layer: &str) {
+/// Changes the layer of one client in the background, failures end up in the event log.
+fn spawn_layer_change(shared_data: &Arc<SharedData>, client: &Client, layer: &str) {
+    let shared_data = shared_data.clone();
+    let name = client.name.clone();
+    let ip = client.ip_address;
     let port = shared_data.config.kanata_port;
-    for client in &shared_data.clients {
-        let ip = client.ip_address;
-        let layer = layer.to_string();
-        let timeout = Duration::from_millis(shared_data.config.kanata_tcp_timeout_ms);
-        thread::spawn(move || try_layer_change(ip, port, &layer, timeout));
+    let layer = layer.to_string();
+    let timeout = Duration::from_millis(shared_data.config.kanata_tcp_timeout_ms);
+    thread::spawn(move || {
+        if let Err(err) = try_layer_change(ip, port, &layer, timeout) {
+            log_layer_change_failure(&shared_data, &name, &layer, err);
+        }
+    });
+}
+
+fn log_layer_change_failure(shared_data: &SharedData, name: &str, layer: &str, err: io::Error) {
+    shared_data.log(
+        Severity::Error,
+        "kanata",
+        format!("could not switch {} to layer '{}': {}", name, layer, err),
+    );
+}
+
+/// Keyboards of finished clients stay disabled, only disabling still reaches them.
+pub fn keeps_finished_layer(client: &Client, layer: &str) -> bool {
+    layer != "disabled" && client.finished.lock().unwrap().is_some()
+}
+
+/// Clients that finished early keep their disabled keyboard, whatever `layer` is.
+pub fn try_layer_change_all(shared_data: Arc<SharedData>, layer: &str) {
+    let (finished, clients): (Vec<&Client>, Vec<&Client>) = shared_data
+        .clients
+        .iter()
+        .partition(|client| keeps_finished_layer(client, layer));
+    let mut description = format!("all keyboards set to layer '{}'", layer);
+    if !finished.is_empty() {
+        let names = finished
+            .iter()
+            .map(|client| client.name.as_str())
+            .collect::<Vec<_>>();
+        description.push_str(&format!(", except finished {}", names.join(", ")));
+    }
+    shared_data.record_event(RecordCategory::Keyboards, description);
+    for client in clients {
+        client.intend_layer(layer);
+        spawn_layer_change(&shared_data, client, layer);
     }
+    persist_session(&shared_data);
+}
+
+pub fn try_layer_change_client(shared_data: &Arc<SharedData>, client: &Client, layer: &str) {
+    shared_data.record_event(
+        RecordCategory::Keyboards,
+        format!("keyboard of {} set to layer '{}'", client.name, layer),
+    );
+    client.intend_layer(layer);
+    persist_session(shared_data);
+    spawn_layer_change(shared_data, client, layer);
 }
 
 pub fn enable_keyboards(shared_data: Arc<SharedData>) {
@@ -50,12 +107,12 @@ fn get_current_layer(client: &Client, port: u16, timeout: Duration) -> Option<St
     .ok()?;
 
     let mut buf = [0; 1024];
-    stream.read(&mut buf).ok()?;
+    let length = stream.read(&mut buf).ok()?;
     // write invalid message to disconnect from kanata - otherwise it accumulates connections
     let _ = stream.write("{}".as_bytes());
-    let msg_str = String::from_utf8_lossy(&buf);
+    let msg_str = String::from_utf8_lossy(&buf[..length]);
     let msg_trim = msg_str.lines().next()?;
-    let msg_json: Value = serde_json::from_str(&msg_trim).unwrap();
+    let msg_json: Value = serde_json::from_str(msg_trim).ok()?;
     let new_layer = msg_json["LayerChange"]["new"].as_str()?;
 
     Some(new_layer.to_string())
@@ -71,7 +128,105 @@ fn update_client(shared_data: Arc<SharedData>, client_index: usize) {
         shared_data.config.kanata_port,
         Duration::from_millis(shared_data.config.kanata_tcp_timeout_ms),
     );
-    *client.current_layer.lock().unwrap() = current_layer;
+    let previous_layer = std::mem::replace(
+        &mut *client.current_layer.lock().unwrap(),
+        current_layer.clone(),
+    );
+    // unreachable clients come and go, only actual layer switches are worth recording
+    match (previous_layer, current_layer) {
+        (Some(previous_layer), Some(current_layer)) if previous_layer != current_layer => {
+            shared_data.record_event(
+                RecordCategory::Keyboards,
+                format!(
+                    "{} switched from layer '{}' to '{}'",
+                    client.name, previous_layer, current_layer
+                ),
+            );
+        }
+        (Some(_), None) => shared_data.log(
+            Severity::Warning,
+            "kanata",
+            format!("{} is not reachable anymore", client.name),
+        ),
+        (None, Some(_)) => shared_data.log(
+            Severity::Info,
+            "kanata",
+            format!("{} is reachable", client.name),
+        ),
+        _ => {}
+    }
+    reconcile_layer(&shared_data, client);
+}
+
+/// Re-sends the intended layer to a client whose kanata came back in another layer,
+/// e.g. after a restart of the service. A drift is reported once, retries back off.
+fn reconcile_layer(shared_data: &SharedData, client: &Client) {
+    let Some(intended) = client.intended_layer.lock().unwrap().clone() else {
+        return;
+    };
+    let Some(current_layer) = client.current_layer.lock().unwrap().clone() else {
+        return;
+    };
+    // a finished student only ever gets the keyboard locked again
+    if client.finished.lock().unwrap().is_some() && intended.layer != "disabled" {
+        return;
+    }
+    let mut correction = client.layer_correction.lock().unwrap();
+    if current_layer == intended.layer {
+        if correction.take().is_some() {
+            shared_data.log(
+                Severity::Info,
+                "kanata",
+                format!("{} is back in layer '{}'", client.name, intended.layer),
+            );
+        }
+        return;
+    }
+    // a layer change that was just sent may not have arrived yet
+    let grace =
+        chrono::Duration::seconds(shared_data.config.kanata_client_scan_interval_seconds as i64);
+    let now = Utc::now();
+    if now - intended.since < grace {
+        return;
+    }
+    let first_attempt = match correction.as_mut() {
+        Some(previous) if previous.drifted_to == current_layer => {
+            let backoff = grace * 2i32.pow(previous.attempts.min(MAX_BACKOFF_DOUBLINGS));
+            if now - previous.last_attempt < backoff {
+                return;
+            }
+            previous.attempts += 1;
+            previous.last_attempt = now;
+            false
+        }
+        _ => {
+            *correction = Some(LayerCorrection {
+                drifted_to: current_layer.clone(),
+                attempts: 1,
+                last_attempt: now,
+            });
+            true
+        }
+    };
+    drop(correction);
+    if first_attempt {
+        let description = format!(
+            "{} drifted to layer '{}', restoring '{}'",
+            client.name, current_layer, intended.layer
+        );
+        shared_data.log(Severity::Warning, "kanata", description.clone());
+        shared_data.record_event(RecordCategory::Keyboards, description);
+    }
+    let result = try_layer_change(
+        client.ip_address,
+        shared_data.config.kanata_port,
+        &intended.layer,
+        Duration::from_millis(shared_data.config.kanata_tcp_timeout_ms),
+    );
+    // failures of the retries would only repeat the first one
+    if let (Err(err), true) = (result, first_attempt) {
+        log_layer_change_failure(shared_data, &client.name, &intended.layer, err);
+    }
 }
 
 fn update_clients(shared_data: Arc<SharedData>) {
@@ -1,25 +1,48 @@
 use std::{env, process::exit, sync::Arc};
 
+use collection::start_collection_thread;
+use event_log::Severity;
+use exam::{parse_start_time, schedule_start, start_scheduler_thread};
 use input_parser::{
-    create_default_config_if_necessary, get_ip_addresses_of_room, get_rooms,
-    get_symlink_info_of_room, parse_config, room_exists, Config,
+    create_default_config_if_necessary, get_collection_info_of_room, get_groups_of_room,
+    get_ip_addresses_of_room, get_layout_of_room, get_rooms, get_symlink_info_of_room,
+    parse_config, room_exists, Config,
 };
 use kanata_tcp::start_client_update_thread;
-use persistance::get_persisted_time;
+use persistance::{get_persisted_record, get_persisted_time, restore_persisted_session};
+use roster::load_roster;
 use shared_data::SharedData;
 use symlinks::update_symlink_status;
 use timing_webserver::start_webserver_thread;
+use tui_action::{check_hold_duration, KeyBindings};
 
 mod client;
+mod collection;
+mod event_log;
+mod exam;
+mod exam_record;
+mod export;
 mod input_parser;
 mod kanata_tcp;
 mod nonclient_timeraccess;
 mod persistance;
+mod roster;
 mod shared_data;
+mod static_files;
+mod submission;
 mod symlinks;
+#[cfg(test)]
+mod test_util;
+mod time_adjustment;
 mod timing_webserver;
 mod tui;
+mod tui_action;
 mod tui_basic;
+mod tui_bigdigits;
+mod tui_client_table;
+mod tui_input;
+mod tui_seatmap;
+mod undo;
 
 const DEFAULT_CONFIG_CONTENT: &str = include_str!("../res/ppmngr_cfg_default.json");
 const CONFIG_RUNTIME_PATH: &str = "ppmngr_cfg.json";
@@ -28,39 +51,101 @@ fn main() {
     create_default_config_if_necessary(CONFIG_RUNTIME_PATH, DEFAULT_CONFIG_CONTENT);
     let config = parse_config(CONFIG_RUNTIME_PATH).expect("failed to parse config");
     let args = env::args().collect::<Vec<_>>();
-    if args.len() != 2 || args[1] == "-h" || args[1] == "--help" {
+    let valid_args = args.len() == 2 || (args.len() == 4 && args[2] == "--start-at");
+    if !valid_args || args[1] == "-h" || args[1] == "--help" {
         print_usage(&config);
         exit(0);
     }
     let room = &args[1];
+    let scheduled_start = match args.get(3) {
+        Some(time) => match parse_start_time(time) {
+            Ok(start) => Some(start),
+            Err(err) => {
+                println!("Invalid start time: {}", err);
+                exit(1);
+            }
+        },
+        None => None,
+    };
     if !room_exists(room, &config) {
         print_available_rooms(&config);
         exit(1);
     }
-    let clients =
-        get_ip_addresses_of_room(&room, &config).expect(&format!("Room '{}' does not exist", room));
-    let symlink_info = get_symlink_info_of_room(&room, &config)
+    let clients = get_ip_addresses_of_room(room, &config)
+        .unwrap_or_else(|| panic!("Room '{}' does not exist", room));
+    let symlink_info = get_symlink_info_of_room(room, &config)
         .expect("this should be safe at this point, can only fail if room would not exist");
+    let collection_info = get_collection_info_of_room(room, &config);
+    let room_layout = get_layout_of_room(room, &config);
+    let groups = get_groups_of_room(room, &config);
+    for group in &groups {
+        for client in clients
+            .iter()
+            .filter(|client| group.clients.contains(&client.name))
+        {
+            *client.group.lock().unwrap() = Some(group.name.clone());
+        }
+    }
     let persisted_time = get_persisted_time();
+    let persisted_record = get_persisted_record().unwrap_or_default();
+    let record_chain_valid = persisted_record.verify();
     let shared_data = Arc::new(SharedData::new(
         config,
+        room.clone(),
         clients,
         symlink_info,
+        collection_info,
+        groups,
         persisted_time,
+        persisted_record,
     ));
+    if !record_chain_valid {
+        shared_data.log(
+            Severity::Error,
+            "record",
+            "the hash chain of the persisted exam record is broken".to_string(),
+        );
+    }
+    if let Some(roster_path) = &shared_data.config.roster_path {
+        match load_roster(roster_path) {
+            Ok(roster) => *shared_data.roster.lock().unwrap() = roster,
+            Err(err) => {
+                println!("Failed to load roster '{}': {}", roster_path, err);
+                exit(1);
+            }
+        }
+    }
+    let key_bindings = match KeyBindings::new(&shared_data.config.keybindings) {
+        Ok(key_bindings) => key_bindings,
+        Err(err) => {
+            println!("Invalid key bindings: {}", err);
+            exit(1);
+        }
+    };
+    let config = &shared_data.config;
+    if let Err(err) = check_hold_duration(&config.tui_confirmations, config.tui_confirmation_ms) {
+        println!("Invalid confirmations: {}", err);
+        exit(1);
+    }
+    restore_persisted_session(&shared_data);
+    if scheduled_start.is_some() {
+        schedule_start(&shared_data, scheduled_start);
+    }
     update_symlink_status(shared_data.clone());
     start_webserver_thread(shared_data.clone());
     start_client_update_thread(shared_data.clone());
-    tui::tui_main(shared_data).unwrap();
+    start_collection_thread(shared_data.clone());
+    start_scheduler_thread(shared_data.clone());
+    tui::tui_main(shared_data, room_layout, key_bindings).unwrap();
 }
 
 fn print_usage(config: &Config) {
-    println!("Usage: pp_klausur_manager <room>");
+    println!("Usage: pp_klausur_manager <room> [--start-at HH:MM]");
     print_available_rooms(config);
 }
 
 fn print_available_rooms(config: &Config) {
-    let rooms = get_rooms(&config);
+    let rooms = get_rooms(config);
     println!(
         "Available rooms are: [{}]",
         rooms
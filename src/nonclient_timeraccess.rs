@@ -5,14 +5,45 @@ use chrono::{DateTime, Utc};
 #[derive(Debug)]
 pub struct NonclientTimerAccess {
     pub ip_address: Ipv4Addr,
+    pub first_timer_access: DateTime<Utc>,
     pub last_timer_access: DateTime<Utc>,
+    pub hit_count: u32,
+    /// hits the supervisor has already seen in the alert, everything above raises it again
+    pub acknowledged_hits: u32,
+    pub user_agent: Option<String>,
+    pub last_path: String,
 }
 
 impl NonclientTimerAccess {
-    pub fn new(ip_address: Ipv4Addr, last_timer_access: DateTime<Utc>) -> Self {
+    pub fn new(
+        ip_address: Ipv4Addr,
+        last_timer_access: DateTime<Utc>,
+        user_agent: Option<String>,
+        path: String,
+    ) -> Self {
         NonclientTimerAccess {
             ip_address,
+            first_timer_access: last_timer_access,
             last_timer_access,
+            hit_count: 1,
+            acknowledged_hits: 0,
+            user_agent,
+            last_path: path,
         }
     }
+
+    pub fn register_hit(&mut self, time: DateTime<Utc>, user_agent: Option<String>, path: String) {
+        self.last_timer_access = time;
+        self.hit_count += 1;
+        self.user_agent = user_agent;
+        self.last_path = path;
+    }
+
+    pub fn is_unacknowledged(&self) -> bool {
+        self.hit_count > self.acknowledged_hits
+    }
+
+    pub fn acknowledge(&mut self) {
+        self.acknowledged_hits = self.hit_count;
+    }
 }
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs::{self, read_to_string},
     path::Path,
 };
@@ -6,7 +7,19 @@ use std::{
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    client::{IntendedLayer, Note},
+    collection::CollectionStatus,
+    exam_record::ExamRecord,
+    input_parser::TaskDescriptionMode,
+    roster::Student,
+    shared_data::SharedData,
+    submission::Submission,
+};
+
 const PERSISTANCE_PATH: &str = "pp.save";
+const RECORD_PERSISTANCE_PATH: &str = "pp.record";
+const SESSION_PERSISTANCE_PATH: &str = "pp.session";
 
 #[derive(Debug, Deserialize, Serialize)]
 struct SaveState {
@@ -14,6 +27,45 @@ struct SaveState {
     duration_min: u32,
 }
 
+/// Everything the supervisor entered, per client state is keyed by client name.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SessionState {
+    #[serde(default)]
+    clients: HashMap<String, ClientState>,
+    #[serde(default)]
+    scheduled_start: Option<DateTime<Utc>>,
+    #[serde(default)]
+    phase: usize,
+    #[serde(default)]
+    phase_started: Option<DateTime<Utc>>,
+    #[serde(default)]
+    last_collection: Option<DateTime<Utc>>,
+    /// target served under `/task/` in HTTP mode
+    #[serde(default)]
+    http_task_description: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ClientState {
+    #[serde(default)]
+    notes: Vec<Note>,
+    #[serde(default)]
+    student: Option<Student>,
+    #[serde(default)]
+    start_override: Option<DateTime<Utc>>,
+    #[serde(default)]
+    finished: Option<DateTime<Utc>>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    intended_layer: Option<IntendedLayer>,
+    /// the files themselves stay in `submission_directory`
+    #[serde(default)]
+    submissions: Vec<Submission>,
+    #[serde(default)]
+    collection_status: Option<CollectionStatus>,
+}
+
 pub fn get_persisted_time() -> Option<(DateTime<Utc>, Duration)> {
     let filecontent = read_to_string(PERSISTANCE_PATH).ok()?;
     let save_state = serde_json::from_str::<SaveState>(&filecontent).ok()?;
@@ -40,3 +92,97 @@ pub fn delete_persisted_time() {
         let _ = fs::remove_file(path);
     }
 }
+
+pub fn get_persisted_record() -> Option<ExamRecord> {
+    let filecontent = read_to_string(RECORD_PERSISTANCE_PATH).ok()?;
+    serde_json::from_str::<ExamRecord>(&filecontent).ok()
+}
+
+pub fn persist_record(record: &ExamRecord) {
+    if let Ok(json) = serde_json::to_string(record) {
+        let _ = fs::write(RECORD_PERSISTANCE_PATH, json);
+    }
+}
+
+pub fn delete_persisted_record() {
+    let path = Path::new(RECORD_PERSISTANCE_PATH);
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Applies a previously persisted session to the clients of `shared_data`.
+pub fn restore_persisted_session(shared_data: &SharedData) {
+    let Some(session) = read_to_string(SESSION_PERSISTANCE_PATH)
+        .ok()
+        .and_then(|filecontent| serde_json::from_str::<SessionState>(&filecontent).ok())
+    else {
+        return;
+    };
+    *shared_data.scheduled_start.lock().unwrap() = session.scheduled_start;
+    *shared_data.phase.lock().unwrap() = session.phase;
+    *shared_data.phase_started.lock().unwrap() = session.phase_started;
+    *shared_data.last_collection.lock().unwrap() = session.last_collection;
+    if shared_data.symlink_info.mode == TaskDescriptionMode::Http {
+        *shared_data.symlink_target.lock().unwrap() = session.http_task_description;
+    }
+    for client in &shared_data.clients {
+        if let Some(state) = session.clients.get(&client.name) {
+            *client.notes.lock().unwrap() = state.notes.clone();
+            *client.student.lock().unwrap() = state.student.clone();
+            *client.start_override.lock().unwrap() = state.start_override;
+            *client.finished.lock().unwrap() = state.finished;
+            *client.group.lock().unwrap() = state.group.clone();
+            *client.intended_layer.lock().unwrap() = state.intended_layer.clone();
+            *client.submissions.lock().unwrap() = state.submissions.clone();
+            // a collection that was running is not resumed after a restart
+            *client.collection_status.lock().unwrap() = match &state.collection_status {
+                Some(CollectionStatus::Running) => Some(CollectionStatus::Failed(
+                    "interrupted by a restart".to_string(),
+                )),
+                status => status.clone(),
+            };
+        }
+    }
+}
+
+pub fn persist_session(shared_data: &SharedData) {
+    let clients = shared_data
+        .clients
+        .iter()
+        .map(|client| {
+            let state = ClientState {
+                notes: client.notes.lock().unwrap().clone(),
+                student: client.student.lock().unwrap().clone(),
+                start_override: *client.start_override.lock().unwrap(),
+                finished: *client.finished.lock().unwrap(),
+                group: client.group.lock().unwrap().clone(),
+                intended_layer: client.intended_layer.lock().unwrap().clone(),
+                submissions: client.submissions.lock().unwrap().clone(),
+                collection_status: client.collection_status.lock().unwrap().clone(),
+            };
+            (client.name.clone(), state)
+        })
+        .collect();
+    let session = SessionState {
+        clients,
+        scheduled_start: *shared_data.scheduled_start.lock().unwrap(),
+        phase: *shared_data.phase.lock().unwrap(),
+        phase_started: *shared_data.phase_started.lock().unwrap(),
+        last_collection: *shared_data.last_collection.lock().unwrap(),
+        http_task_description: match shared_data.symlink_info.mode {
+            TaskDescriptionMode::Http => shared_data.symlink_target.lock().unwrap().clone(),
+            TaskDescriptionMode::Symlink => None,
+        },
+    };
+    if let Ok(json) = serde_json::to_string(&session) {
+        let _ = fs::write(SESSION_PERSISTANCE_PATH, json);
+    }
+}
+
+pub fn delete_persisted_session() {
+    let path = Path::new(SESSION_PERSISTANCE_PATH);
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
+}
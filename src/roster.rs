@@ -0,0 +1,158 @@
+use std::{fs::read_to_string, io};
+
+use serde::{Deserialize, Serialize};
+
+/// A student of the exam roster.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Student {
+    pub matriculation_number: String,
+    pub name: String,
+}
+
+impl Student {
+    pub fn display(&self) -> String {
+        if self.name.is_empty() {
+            self.matriculation_number.clone()
+        } else {
+            format!("{} ({})", self.name, self.matriculation_number)
+        }
+    }
+}
+
+/// Reads a roster CSV with the columns matriculation number and name.
+///
+/// Both `,` and `;` are accepted as separator, a header line is skipped if its first
+/// column is not a number. Further columns are ignored.
+pub fn load_roster(path: &str) -> io::Result<Vec<Student>> {
+    let students = parse_roster(&read_to_string(path)?);
+    if students.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no students found in roster",
+        ));
+    }
+    Ok(students)
+}
+
+fn parse_roster(filecontent: &str) -> Vec<Student> {
+    filecontent
+        .lines()
+        .map(|line| {
+            let separator = if line.contains(';') { ';' } else { ',' };
+            line.split(separator)
+                .map(|column| column.trim().trim_matches('"').trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|columns| columns.len() >= 2)
+        .filter(|columns| !columns[0].is_empty() && columns[0].chars().all(|c| c.is_ascii_digit()))
+        .map(|columns| Student {
+            matriculation_number: columns[0].clone(),
+            name: columns[1].clone(),
+        })
+        .collect()
+}
+
+/// Finds a student by matriculation number, without roster any number is accepted.
+pub fn find_student(roster: &[Student], matriculation_number: &str) -> Option<Student> {
+    if roster.is_empty() {
+        return Some(Student {
+            matriculation_number: matriculation_number.to_string(),
+            name: String::new(),
+        });
+    }
+    roster
+        .iter()
+        .find(|student| student.matriculation_number == matriculation_number)
+        .cloned()
+}
+
+/// Finds a student by exact matriculation number or by a part of the name that
+/// matches only one student of the roster.
+pub fn search_student(roster: &[Student], query: &str) -> Option<Student> {
+    if let Some(student) = roster
+        .iter()
+        .find(|student| student.matriculation_number == query)
+    {
+        return Some(student.clone());
+    }
+    let query = query.to_lowercase();
+    let mut matches = roster
+        .iter()
+        .filter(|student| student.name.to_lowercase().contains(&query));
+    match (matches.next(), matches.next()) {
+        (Some(student), None) => Some(student.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn student(matriculation_number: &str, name: &str) -> Student {
+        Student {
+            matriculation_number: matriculation_number.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_comma_and_semicolon_separated_rosters() {
+        let roster = "Matrikelnummer,Name\n2412345,Ada Lovelace\n2412346, Alan Turing ,extra\n";
+        assert_eq!(
+            parse_roster(roster),
+            vec![
+                student("2412345", "Ada Lovelace"),
+                student("2412346", "Alan Turing")
+            ]
+        );
+        let roster = "\"2412345\";\"Lovelace, Ada\"\r\n\r\n2412346;Turing, Alan\r\n";
+        assert_eq!(
+            parse_roster(roster),
+            vec![
+                student("2412345", "Lovelace, Ada"),
+                student("2412346", "Turing, Alan")
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_headers_and_malformed_lines() {
+        let roster = "number;name\n;no number\n24a;letters\n2412345\n2412346;Alan Turing";
+        assert_eq!(
+            parse_roster(roster),
+            vec![student("2412346", "Alan Turing")]
+        );
+        assert!(parse_roster("").is_empty());
+    }
+
+    #[test]
+    fn finds_students_by_number_or_unique_name() {
+        let roster = vec![
+            student("2412345", "Ada Lovelace"),
+            student("2412346", "Alan Turing"),
+            student("2412347", "Grace Hopper"),
+        ];
+        assert_eq!(
+            find_student(&roster, "2412346"),
+            Some(student("2412346", "Alan Turing"))
+        );
+        assert_eq!(find_student(&roster, "9999999"), None);
+        assert_eq!(
+            search_student(&roster, "hopper"),
+            Some(student("2412347", "Grace Hopper"))
+        );
+        assert_eq!(
+            search_student(&roster, "2412345"),
+            Some(student("2412345", "Ada Lovelace"))
+        );
+        // "a" matches every name
+        assert_eq!(search_student(&roster, "a"), None);
+        assert_eq!(search_student(&roster, "Knuth"), None);
+    }
+
+    #[test]
+    fn accepts_any_number_without_roster() {
+        assert_eq!(find_student(&[], "2412345"), Some(student("2412345", "")));
+    }
+}
@@ -1,44 +1,247 @@
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, Local, Utc};
 
 use crate::{
     client::Client,
-    input_parser::{Config, SymlinkInfo},
+    event_log::{EventLog, Severity},
+    exam_record::{ExamRecord, RecordCategory},
+    input_parser::{CollectionInfo, Config, ExamGroup, Phase, SymlinkInfo},
+    kanata_tcp::try_layer_change_client,
     nonclient_timeraccess::NonclientTimerAccess,
+    persistance::{persist_record, persist_session},
+    roster::Student,
 };
 
 #[derive(Debug)]
 pub struct SharedData {
     pub config: Config,
+    pub room: String,
     pub clients: Vec<Client>,
     pub nonclients: Mutex<Vec<NonclientTimerAccess>>,
     pub times: Mutex<Option<(DateTime<Utc>, Duration)>>,
     pub symlink_info: SymlinkInfo,
     pub symlink_target: Mutex<Option<String>>,
+    pub collection_info: Option<CollectionInfo>,
+    pub last_collection: Mutex<Option<DateTime<Utc>>>,
+    pub record: Mutex<ExamRecord>,
+    pub roster: Mutex<Vec<Student>>,
+    /// the exam starts automatically at this time
+    pub scheduled_start: Mutex<Option<DateTime<Utc>>>,
+    /// index into `config.phases`
+    pub phase: Mutex<usize>,
+    pub phase_started: Mutex<Option<DateTime<Utc>>>,
+    pub groups: Vec<ExamGroup>,
+    /// shared with background threads that only need to report problems
+    pub event_log: EventLog,
 }
 
 impl SharedData {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         config: Config,
+        room: String,
         clients: Vec<Client>,
         symlink_info: SymlinkInfo,
+        collection_info: Option<CollectionInfo>,
+        groups: Vec<ExamGroup>,
         times: Option<(DateTime<Utc>, Duration)>,
+        record: ExamRecord,
     ) -> Self {
+        let event_log = EventLog::new(config.event_log_file.as_deref());
         SharedData {
             config,
+            room,
             clients,
             nonclients: Mutex::new(Vec::new()),
             times: Mutex::new(times),
             symlink_info,
             symlink_target: Mutex::new(None),
+            collection_info,
+            last_collection: Mutex::new(None),
+            record: Mutex::new(record),
+            roster: Mutex::new(Vec::new()),
+            scheduled_start: Mutex::new(None),
+            phase: Mutex::new(0),
+            phase_started: Mutex::new(None),
+            groups,
+            event_log,
         }
     }
 
+    /// Adds an entry to the event log panel of the TUI.
+    pub fn log(&self, severity: Severity, source: &'static str, message: String) {
+        self.event_log.push(severity, source, message);
+    }
+
+    /// Appends an event to the exam record and persists it immediately.
+    pub fn record_event(&self, category: RecordCategory, description: String) {
+        let mut record = self.record.lock().unwrap();
+        record.append(category, description);
+        persist_record(&record);
+    }
+
+    pub fn current_phase(&self) -> Option<&Phase> {
+        self.config.phases.get(*self.phase.lock().unwrap())
+    }
+
+    /// end of the countdown of the current phase, if it has a duration
+    pub fn phase_end(&self) -> Option<DateTime<Utc>> {
+        let duration = self.current_phase()?.duration_minutes?;
+        let phase_started = (*self.phase_started.lock().unwrap())?;
+        Some(phase_started + Duration::minutes(duration))
+    }
+
+    /// Seats `student` at `client`, removing them from any other seat, `None` frees the seat.
+    pub fn assign_student(&self, client: &Client, student: Option<Student>) {
+        if let Some(student) = &student {
+            for other in &self.clients {
+                let mut other_student = other.student.lock().unwrap();
+                if other_student.as_ref() == Some(student) {
+                    *other_student = None;
+                }
+            }
+        }
+        let description = match &student {
+            Some(student) => format!("{} seated at {}", student.display(), client.name),
+            None => format!("seat {} freed", client.name),
+        };
+        *client.student.lock().unwrap() = student;
+        self.record_event(RecordCategory::Student, description);
+        persist_session(self);
+    }
+
     pub fn finish_time_as_unix(&self) -> Option<i64> {
         self.times
             .lock()
             .unwrap()
             .map(|(start_time, duration)| (start_time + duration).timestamp())
     }
+
+    pub fn group_of_client(&self, client: &Client) -> Option<&ExamGroup> {
+        let group = client.group.lock().unwrap();
+        self.groups
+            .iter()
+            .find(|candidate| Some(&candidate.name) == group.as_ref())
+    }
+
+    /// Duration of `group` while the room runs for `duration`, adjustments of the room
+    /// apply to every group alike.
+    pub fn duration_of_group(&self, group: Option<&ExamGroup>, duration: Duration) -> Duration {
+        match group {
+            Some(group) => {
+                duration
+                    + Duration::minutes(group.duration_minutes - self.config.timer_duration_minutes)
+            }
+            None => duration,
+        }
+    }
+
+    /// Refuses a room duration that leaves a group without time. Groups can finish earlier
+    /// than the room, which [`crate::time_adjustment::apply_adjustment`] checks.
+    pub fn check_group_durations(
+        &self,
+        start_time: DateTime<Utc>,
+        duration: Duration,
+    ) -> Result<(), String> {
+        let now = Utc::now();
+        match self
+            .groups
+            .iter()
+            .find(|group| start_time + self.duration_of_group(Some(group), duration) < now)
+        {
+            Some(group) => Err(format!(
+                "the exam of group '{}' would already be over",
+                group.name
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Finish of `client`, differs from the finish of the room if it started individually
+    /// or belongs to a group.
+    pub fn finish_time_of_client(&self, client: &Client) -> Option<DateTime<Utc>> {
+        let (start_time, duration) = (*self.times.lock().unwrap())?;
+        let start_time = client.start_override.lock().unwrap().unwrap_or(start_time);
+        Some(start_time + self.duration_of_group(self.group_of_client(client), duration))
+    }
+
+    /// Finish of the client that finishes last, the room is done afterwards.
+    pub fn latest_finish_time(&self) -> Option<DateTime<Utc>> {
+        let room_finish = self
+            .times
+            .lock()
+            .unwrap()
+            .map(|(start_time, duration)| start_time + duration);
+        self.clients
+            .iter()
+            .filter_map(|client| self.finish_time_of_client(client))
+            .chain(room_finish)
+            .max()
+    }
+
+    /// Marks `client` as handed in and locks its keyboard, returns false if it already was or
+    /// the exam has not started.
+    pub fn finish_client(self: &Arc<Self>, client: &Client) -> bool {
+        if self.times.lock().unwrap().is_none() {
+            return false;
+        }
+        let mut finished = client.finished.lock().unwrap();
+        if finished.is_some() {
+            return false;
+        }
+        *finished = Some(Utc::now());
+        drop(finished);
+        self.record_event(
+            RecordCategory::Timer,
+            format!("{} finished early", client.name),
+        );
+        try_layer_change_client(self, client, "disabled");
+        persist_session(self);
+        true
+    }
+
+    /// Takes back an early finish, the keyboard is enabled again while time is left.
+    pub fn reopen_client(self: &Arc<Self>, client: &Client) {
+        if client.finished.lock().unwrap().take().is_none() {
+            return;
+        }
+        self.record_event(
+            RecordCategory::Timer,
+            format!("early finish of {} revoked", client.name),
+        );
+        if self
+            .finish_time_of_client(client)
+            .is_some_and(|finish_time| Utc::now() < finish_time)
+        {
+            try_layer_change_client(self, client, "enabled");
+        }
+        persist_session(self);
+    }
+
+    /// Moves `client` into the group `name`, `None` returns it to the default duration.
+    pub fn set_group(&self, client: &Client, name: Option<String>) {
+        let description = match &name {
+            Some(name) => format!("{} moved to group '{}'", client.name, name),
+            None => format!("{} moved to the default duration", client.name),
+        };
+        *client.group.lock().unwrap() = name;
+        self.record_event(RecordCategory::Timer, description);
+        persist_session(self);
+    }
+
+    /// Gives `client` its own start time, `None` returns it to the start of the room.
+    pub fn set_start_override(&self, client: &Client, start: Option<DateTime<Utc>>) {
+        *client.start_override.lock().unwrap() = start;
+        let description = match start {
+            Some(start) => format!(
+                "individual start of {} set to {}",
+                client.name,
+                start.with_timezone(&Local).format("%H:%M:%S")
+            ),
+            None => format!("individual start of {} removed", client.name),
+        };
+        self.record_event(RecordCategory::Timer, description);
+        persist_session(self);
+    }
 }
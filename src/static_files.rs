@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+/// Maps the path part of a request url to a file inside of `root`.
+///
+/// Returns `None` if the path is malformed, leaves `root` or does not point to a file.
+/// Directories are resolved to their `index.html`.
+pub fn resolve_path(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let url_path = url_path.split(['?', '#']).next()?;
+    let decoded = percent_decode(url_path)?;
+
+    let mut path = root.to_path_buf();
+    for component in decoded.split('/').filter(|component| !component.is_empty()) {
+        if component == "." || component == ".." || component.contains(['\\', '\0']) {
+            return None;
+        }
+        path.push(component);
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+
+    // symlinks inside of root could still point elsewhere
+    let root = root.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    if path.starts_with(&root) && path.is_file() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html") | Some("htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("json") => "application/json",
+        Some("txt") | Some("md") | Some("java") | Some("py") | Some("c") | Some("h")
+        | Some("rs") => "text/plain; charset=utf-8",
+        Some("pdf") => "application/pdf",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+pub fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// `<tmp>/root` with `index.html`, `sub/file.txt` and `../secret.txt` next to it.
+    fn create_root(name: &str) -> (TempDir, PathBuf) {
+        let base = TempDir::new(name);
+        let root = base.path().join("root");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("index.html"), "index").unwrap();
+        fs::write(root.join("sub/file.txt"), "file").unwrap();
+        fs::write(root.join("sub/with space.txt"), "space").unwrap();
+        fs::write(base.path().join("secret.txt"), "secret").unwrap();
+        (base, root)
+    }
+
+    #[test]
+    fn resolves_files_and_directory_index() {
+        let (_base, root) = create_root("resolve");
+        let canonical_root = root.canonicalize().unwrap();
+        assert_eq!(
+            resolve_path(&root, "sub/file.txt"),
+            Some(canonical_root.join("sub/file.txt"))
+        );
+        assert_eq!(
+            resolve_path(&root, "sub/with%20space.txt?download#top"),
+            Some(canonical_root.join("sub/with space.txt"))
+        );
+        assert_eq!(
+            resolve_path(&root, ""),
+            Some(canonical_root.join("index.html"))
+        );
+        assert_eq!(resolve_path(&root, "sub/missing.txt"), None);
+        // directories without index.html are not listed
+        assert_eq!(resolve_path(&root, "sub/"), None);
+    }
+
+    #[test]
+    fn refuses_paths_leaving_the_root() {
+        let (_base, root) = create_root("traversal");
+        assert_eq!(resolve_path(&root, "../secret.txt"), None);
+        assert_eq!(resolve_path(&root, "sub/../../secret.txt"), None);
+        assert_eq!(resolve_path(&root, "%2e%2e/secret.txt"), None);
+        assert_eq!(resolve_path(&root, "sub%2f..%2f..%2fsecret.txt"), None);
+        assert_eq!(resolve_path(&root, "..%5csecret.txt"), None);
+        assert_eq!(resolve_path(&root, "index.html%00.txt"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_symlinks_leaving_the_root() {
+        let (_base, root) = create_root("symlink");
+        let link = root.join("link.txt");
+        std::os::unix::fs::symlink(root.join("../secret.txt"), &link).unwrap();
+        assert_eq!(resolve_path(&root, "link.txt"), None);
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(percent_decode("a%20b%2Fc"), Some("a b/c".to_string()));
+        assert_eq!(percent_decode("%C3%A4"), Some("ä".to_string()));
+        assert_eq!(percent_decode("%2"), None);
+        assert_eq!(percent_decode("%zz"), None);
+        assert_eq!(percent_decode("%ff"), None);
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::client::Client;
+
+/// A file a client uploaded through the timer webserver.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Submission {
+    pub file_name: String,
+    pub time: DateTime<Utc>,
+    pub size: usize,
+    pub sha256: String,
+}
+
+/// A file extracted from a `multipart/form-data` request body.
+pub struct UploadedFile {
+    pub file_name: String,
+    pub content: Vec<u8>,
+}
+
+/// Extracts the boundary from a `multipart/form-data` content type header value.
+pub fn get_multipart_boundary(content_type: &str) -> Option<String> {
+    let (mime, parameters) = content_type.split_once(';')?;
+    if !mime.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    parameters
+        .split(';')
+        .filter_map(|parameter| parameter.trim().split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim_matches('"').to_string())
+}
+
+/// Returns all parts of a multipart body that carry a non-empty file name.
+pub fn parse_multipart_files(body: &[u8], boundary: &str) -> Vec<UploadedFile> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut files = Vec::new();
+    let mut rest = match find(body, &delimiter) {
+        Some(start) => &body[start + delimiter.len()..],
+        None => return files,
+    };
+    // every part starts with CRLF after the delimiter, the closing delimiter with "--"
+    while let Some(part_and_more) = rest.strip_prefix(b"\r\n") {
+        let Some(part_end) = find(part_and_more, &delimiter) else {
+            break;
+        };
+        let part = &part_and_more[..part_end];
+        rest = &part_and_more[part_end + delimiter.len()..];
+
+        let Some(header_end) = find(part, b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&part[..header_end]);
+        // the content is followed by the CRLF belonging to the next delimiter
+        let content = &part[header_end + 4..];
+        let content = content.strip_suffix(b"\r\n").unwrap_or(content);
+        if let Some(file_name) = headers
+            .lines()
+            .filter(|line| {
+                line.to_ascii_lowercase()
+                    .starts_with("content-disposition:")
+            })
+            .find_map(get_disposition_file_name)
+        {
+            if !file_name.is_empty() {
+                files.push(UploadedFile {
+                    file_name,
+                    content: content.to_vec(),
+                });
+            }
+        }
+    }
+    files
+}
+
+fn get_disposition_file_name(header: &str) -> Option<String> {
+    header
+        .split(';')
+        .filter_map(|parameter| parameter.trim().split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("filename"))
+        .map(|(_, value)| value.trim_matches('"').to_string())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Stores an uploaded file as `<directory>/<client>/<timestamp>_<name>` and records its hash in
+/// `<directory>/<client>/SHA256SUMS`, so the submissions can be verified with `sha256sum -c`.
+///
+/// Files with the same name uploaded within the same millisecond get a counter after the
+/// timestamp, existing files are never overwritten.
+pub fn store_submission(
+    directory: &str,
+    client: &Client,
+    file: &UploadedFile,
+) -> io::Result<Submission> {
+    let client_directory = Path::new(directory).join(&client.name);
+    fs::create_dir_all(&client_directory)?;
+
+    let now = Utc::now();
+    let file_name = sanitize_file_name(&file.file_name);
+    let timestamp = now.with_timezone(&Local).format("%Y%m%d-%H%M%S%.3f");
+    let mut counter = 1;
+    let (stored_name, mut stored_file) = loop {
+        let stored_name = match counter {
+            1 => format!("{}_{}", timestamp, file_name),
+            counter => format!("{}-{}_{}", timestamp, counter, file_name),
+        };
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(client_directory.join(&stored_name))
+        {
+            Ok(stored_file) => break (stored_name, stored_file),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => counter += 1,
+            Err(err) => return Err(err),
+        }
+    };
+    stored_file.write_all(&file.content)?;
+
+    let sha256 = format!("{:x}", Sha256::digest(&file.content));
+    let mut checksums = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(client_directory.join("SHA256SUMS"))?;
+    writeln!(checksums, "{}  {}", sha256, stored_name)?;
+
+    Ok(Submission {
+        file_name,
+        time: now,
+        size: file.content.len(),
+        sha256,
+    })
+}
+
+/// Keeps only the last path component and replaces characters that are awkward in file names.
+fn sanitize_file_name(file_name: &str) -> String {
+    let base_name = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized = base_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '.' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    match sanitized.trim_start_matches('.') {
+        "" => "upload".to_string(),
+        name => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn extracts_the_multipart_boundary() {
+        assert_eq!(
+            get_multipart_boundary("multipart/form-data; boundary=----abc123"),
+            Some("----abc123".to_string())
+        );
+        assert_eq!(
+            get_multipart_boundary("Multipart/Form-Data; charset=utf-8; Boundary=\"x y\""),
+            Some("x y".to_string())
+        );
+        assert_eq!(get_multipart_boundary("multipart/form-data"), None);
+        assert_eq!(get_multipart_boundary("text/plain; boundary=abc"), None);
+    }
+
+    #[test]
+    fn parses_files_and_skips_fields_and_empty_inputs() {
+        let body = b"preamble\r\n--XYZ\r\n\
+            Content-Disposition: form-data; name=\"comment\"\r\n\r\n\
+            not a file\r\n--XYZ\r\n\
+            Content-Disposition: form-data; name=\"files\"; filename=\"a.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            line one\r\nline two\r\n--XYZ\r\n\
+            content-disposition: form-data; name=\"files\"; filename=\"b.bin\"\r\n\r\n\
+            \x00\x01--XY\r\n--XYZ\r\n\
+            Content-Disposition: form-data; name=\"files\"; filename=\"\"\r\n\r\n\
+            \r\n--XYZ--\r\n";
+        let files = parse_multipart_files(body, "XYZ");
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].file_name, "a.txt");
+        assert_eq!(files[0].content, b"line one\r\nline two");
+        assert_eq!(files[1].file_name, "b.bin");
+        assert_eq!(files[1].content, b"\x00\x01--XY");
+    }
+
+    #[test]
+    fn ignores_truncated_bodies() {
+        let body =
+            b"--XYZ\r\nContent-Disposition: form-data; name=\"f\"; filename=\"a.txt\"\r\n\r\nabc";
+        assert!(parse_multipart_files(body, "XYZ").is_empty());
+        assert!(parse_multipart_files(b"no delimiter at all", "XYZ").is_empty());
+    }
+
+    #[test]
+    fn sanitizes_file_names() {
+        assert_eq!(sanitize_file_name("report.pdf"), "report.pdf");
+        assert_eq!(
+            sanitize_file_name("C:\\Users\\exam\\Main.java"),
+            "Main.java"
+        );
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("my file (1).txt"), "my_file__1_.txt");
+        assert_eq!(sanitize_file_name(".."), "upload");
+        assert_eq!(sanitize_file_name(".hidden"), "hidden");
+    }
+
+    #[test]
+    fn keeps_files_with_the_same_name() {
+        let temp_dir = TempDir::new("submission");
+        let directory = temp_dir.path().to_string_lossy().to_string();
+        let client = Client::new("client-01".to_string(), Ipv4Addr::LOCALHOST);
+        let upload = |content: &[u8]| UploadedFile {
+            file_name: "Main.java".to_string(),
+            content: content.to_vec(),
+        };
+        for content in [b"first", b"other"] {
+            store_submission(&directory, &client, &upload(content)).unwrap();
+        }
+        let client_directory = Path::new(&directory).join("client-01");
+        let mut contents = fs::read_dir(&client_directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.to_string_lossy().ends_with("_Main.java"))
+            .map(|path| fs::read(path).unwrap())
+            .collect::<Vec<_>>();
+        contents.sort();
+        assert_eq!(contents, vec![b"first".to_vec(), b"other".to_vec()]);
+        let checksums = fs::read_to_string(client_directory.join("SHA256SUMS")).unwrap();
+        assert_eq!(checksums.lines().count(), 2);
+    }
+}
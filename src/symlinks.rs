@@ -2,6 +2,10 @@ use std::os::unix::fs as unixfs;
 use std::sync::Arc;
 use std::{fs, io, path::Path};
 
+use crate::event_log::Severity;
+use crate::exam_record::RecordCategory;
+use crate::input_parser::TaskDescriptionMode;
+use crate::persistance::persist_session;
 use crate::shared_data::SharedData;
 
 pub fn unlock_taskdescription(shared_data: Arc<SharedData>) {
@@ -14,9 +18,25 @@ pub fn lock_taskdescription(shared_data: Arc<SharedData>) {
     try_set_symlink_target_and_update(shared_data, &dummy_target);
 }
 
-fn try_set_symlink_target_and_update(shared_data: Arc<SharedData>, target: &str) {
+pub fn try_set_symlink_target_and_update(shared_data: Arc<SharedData>, target: &str) {
+    shared_data.record_event(
+        RecordCategory::TaskDescription,
+        format!("task description set to '{}'", target),
+    );
+    if shared_data.symlink_info.mode == TaskDescriptionMode::Http {
+        *shared_data.symlink_target.lock().unwrap() = Some(target.to_string());
+        // unlike a symlink the served target would be lost on a restart
+        persist_session(&shared_data);
+        return;
+    }
     let symlink = shared_data.symlink_info.symlink_path.as_str();
-    let _ = set_symlink_target(target, symlink);
+    if let Err(err) = set_symlink_target(target, symlink) {
+        shared_data.log(
+            Severity::Error,
+            "symlinks",
+            format!("could not point {} to {}: {}", symlink, target, err),
+        );
+    }
     update_symlink_status(shared_data);
 }
 
@@ -38,6 +58,24 @@ fn get_symlink_target(symlink_path: &str) -> Option<String> {
 }
 
 pub fn update_symlink_status(shared_data: Arc<SharedData>) {
+    if shared_data.symlink_info.mode == TaskDescriptionMode::Http {
+        // nothing on disk to look at, the webserver starts out serving the dummy
+        let mut target = shared_data.symlink_target.lock().unwrap();
+        if target.is_none() {
+            *target = Some(shared_data.symlink_info.dummy_target.clone());
+        }
+        return;
+    }
     let target = get_symlink_target(&shared_data.symlink_info.symlink_path);
+    if target.is_none() {
+        shared_data.log(
+            Severity::Warning,
+            "symlinks",
+            format!(
+                "symlink {} is not accessible",
+                shared_data.symlink_info.symlink_path
+            ),
+        );
+    }
     *shared_data.symlink_target.lock().unwrap() = target;
 }
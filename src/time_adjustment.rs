@@ -0,0 +1,183 @@
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone, Utc};
+
+/// A change of the exam duration as entered by the supervisor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Adjustment {
+    /// `+15` or `-5`
+    Relative(Duration),
+    /// `=120`
+    Total(Duration),
+    /// `until 12:30`
+    Until(DateTime<Utc>),
+}
+
+/// `until` refers to a time of tomorrow if it has passed today, as long as it is at most
+/// this far ahead, so an exam can run past midnight without accepting obvious typos.
+const MAX_UNTIL_AHEAD_HOURS: i64 = 12;
+
+/// Parses `+15`, `-5`, `=120` (minutes) or `until 12:30` (local time).
+pub fn parse_adjustment(text: &str) -> Result<Adjustment, String> {
+    parse_adjustment_at(text, Local::now())
+}
+
+fn parse_adjustment_at(text: &str, now: DateTime<Local>) -> Result<Adjustment, String> {
+    let text = text.trim();
+    let minutes = |number: &str| {
+        let number = number.trim();
+        // parse would accept another sign, e.g. "+-5"
+        let digits_only = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+        digits_only
+            .then(|| number.parse::<i64>().ok().and_then(Duration::try_minutes))
+            .flatten()
+            .ok_or(format!("'{}' is not a number of minutes", number))
+    };
+    if let Some(number) = text.strip_prefix('+') {
+        Ok(Adjustment::Relative(minutes(number)?))
+    } else if let Some(number) = text.strip_prefix('-') {
+        Ok(Adjustment::Relative(-minutes(number)?))
+    } else if let Some(number) = text.strip_prefix('=') {
+        Ok(Adjustment::Total(minutes(number)?))
+    } else if let Some(time) = text.strip_prefix("until") {
+        let time = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|_| format!("'{}' is not a time like 12:30", time.trim()))?;
+        let today = now.date_naive();
+        let finish = [Some(today), today.succ_opt()]
+            .into_iter()
+            .flatten()
+            .filter_map(|date| Local.from_local_datetime(&date.and_time(time)).earliest())
+            .find(|finish| *finish > now)
+            .filter(|finish| *finish - now <= Duration::hours(MAX_UNTIL_AHEAD_HOURS))
+            .ok_or(format!("{} has already passed", time.format("%H:%M")))?;
+        Ok(Adjustment::Until(finish.with_timezone(&Utc)))
+    } else {
+        Err("expected +15, -5, =120 or until 12:30".to_string())
+    }
+}
+
+/// Returns the new duration of an exam started at `start_time`, refusing durations
+/// that would already be over.
+pub fn apply_adjustment(
+    start_time: DateTime<Utc>,
+    duration: Duration,
+    adjustment: Adjustment,
+) -> Result<Duration, String> {
+    let new_duration = match adjustment {
+        Adjustment::Relative(change) => duration + change,
+        Adjustment::Total(total) => total,
+        Adjustment::Until(finish) => finish - start_time,
+    };
+    // only whole minutes are persisted, round up so nobody loses time
+    let new_duration = Duration::minutes((new_duration.num_seconds() + 59).div_euclid(60));
+    if start_time + new_duration < Utc::now() {
+        return Err("the exam would already be over".to_string());
+    }
+    Ok(new_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 6, 3, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_relative_and_total_minutes() {
+        let now = local(10, 0);
+        assert_eq!(
+            parse_adjustment_at("+15", now),
+            Ok(Adjustment::Relative(Duration::minutes(15)))
+        );
+        assert_eq!(
+            parse_adjustment_at(" - 5 ", now),
+            Ok(Adjustment::Relative(Duration::minutes(-5)))
+        );
+        assert_eq!(
+            parse_adjustment_at("=120", now),
+            Ok(Adjustment::Total(Duration::minutes(120)))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_minutes() {
+        let now = local(10, 0);
+        for text in [
+            "+-5", "-+5", "--5", "++5", "=-5", "+", "+5min", "+1.5", "15", "",
+        ] {
+            assert!(
+                parse_adjustment_at(text, now).is_err(),
+                "{} was accepted",
+                text
+            );
+        }
+        // too large for a duration
+        assert!(parse_adjustment_at("+99999999999999999", now).is_err());
+    }
+
+    #[test]
+    fn parses_until_today_and_past_midnight() {
+        assert_eq!(
+            parse_adjustment_at("until 12:30", local(10, 0)),
+            Ok(Adjustment::Until(local(12, 30).with_timezone(&Utc)))
+        );
+        let after_midnight = Local.with_ymd_and_hms(2024, 6, 4, 0, 30, 0).unwrap();
+        assert_eq!(
+            parse_adjustment_at("until 00:30", local(23, 0)),
+            Ok(Adjustment::Until(after_midnight.with_timezone(&Utc)))
+        );
+        // more likely a typo than an exam running until tomorrow morning
+        assert_eq!(
+            parse_adjustment_at("until 09:30", local(10, 0)),
+            Err("09:30 has already passed".to_string())
+        );
+        assert!(parse_adjustment_at("until 25:00", local(10, 0)).is_err());
+        assert!(parse_adjustment_at("until", local(10, 0)).is_err());
+    }
+
+    #[test]
+    fn applies_adjustments_in_whole_minutes() {
+        let start_time = Utc::now() - Duration::minutes(30);
+        let duration = Duration::minutes(90);
+        assert_eq!(
+            apply_adjustment(
+                start_time,
+                duration,
+                Adjustment::Relative(Duration::minutes(15))
+            ),
+            Ok(Duration::minutes(105))
+        );
+        assert_eq!(
+            apply_adjustment(
+                start_time,
+                duration,
+                Adjustment::Total(Duration::minutes(60))
+            ),
+            Ok(Duration::minutes(60))
+        );
+        // rounded up so nobody loses time
+        let finish = start_time + Duration::minutes(100) + Duration::seconds(1);
+        assert_eq!(
+            apply_adjustment(start_time, duration, Adjustment::Until(finish)),
+            Ok(Duration::minutes(101))
+        );
+    }
+
+    #[test]
+    fn refuses_durations_that_are_already_over() {
+        let start_time = Utc::now() - Duration::minutes(30);
+        let duration = Duration::minutes(90);
+        assert!(apply_adjustment(
+            start_time,
+            duration,
+            Adjustment::Relative(Duration::minutes(-61))
+        )
+        .is_err());
+        assert!(apply_adjustment(
+            start_time,
+            duration,
+            Adjustment::Total(Duration::minutes(20))
+        )
+        .is_err());
+    }
+}
@@ -1,9 +1,36 @@
-use std::{sync::Arc, thread};
+use std::{
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::{
+        mpsc::{self, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+};
 
-use chrono::Utc;
-use tiny_http::{Header, Response, Server};
+use chrono::{Duration, Local, Utc};
+use tiny_http::{Header, Method, Request, Response, ResponseBox, Server};
 
-use crate::{nonclient_timeraccess::NonclientTimerAccess, shared_data::SharedData};
+use crate::{
+    client::Client,
+    event_log::Severity,
+    exam_record::RecordCategory,
+    input_parser::{FilesAvailability, TaskDescriptionMode},
+    nonclient_timeraccess::NonclientTimerAccess,
+    persistance::persist_session,
+    roster::find_student,
+    shared_data::SharedData,
+    static_files::{content_type, percent_decode, resolve_path},
+    submission::{get_multipart_boundary, parse_multipart_files, store_submission},
+};
+
+const FILES_URL_PREFIX: &str = "/files/";
+const TASK_URL_PREFIX: &str = "/task/";
+/// uploads are received by this many threads while the timer page keeps being served
+const SUBMISSION_WORKERS: usize = 4;
+/// uploads waiting for a worker, further ones are refused until the queue drains
+const SUBMISSION_QUEUE_LENGTH: usize = 16;
 
 pub fn start_webserver_thread(shared_data: Arc<SharedData>) {
     thread::spawn(|| webserver(shared_data));
@@ -11,80 +38,551 @@ pub fn start_webserver_thread(shared_data: Arc<SharedData>) {
 
 fn webserver(shared_data: Arc<SharedData>) {
     let port = shared_data.config.timer_port;
-    let server = Server::http(format!("0.0.0.0:{}", port)).unwrap();
+    let server = match Server::http(format!("0.0.0.0:{}", port)) {
+        Ok(server) => server,
+        Err(err) => {
+            shared_data.log(
+                Severity::Error,
+                "webserver",
+                format!("could not listen on port {}: {}", port, err),
+            );
+            return;
+        }
+    };
+    shared_data.log(
+        Severity::Info,
+        "webserver",
+        format!("listening on port {}", port),
+    );
 
-    for request in server.incoming_requests() {
+    let submissions = start_submission_workers(&shared_data);
+    for mut request in server.incoming_requests() {
         let response = match request.url() {
             "/" => {
-                // store access time
-                let mut is_valid_client = false;
-                if let Some(std::net::SocketAddr::V4(sockaddr)) = request.remote_addr() {
-                    let remote_ip = sockaddr.ip();
-                    let now = Utc::now();
-                    let mut time_stored = false;
-                    // check if request comes from registered client
-                    for client in &shared_data.clients {
-                        if client.ip_address.eq(remote_ip) {
-                            *client.last_timer_access.lock().unwrap() = Some(now);
-                            is_valid_client = true;
-                            time_stored = true;
-                        }
-                    }
-                    // check if request comes from unregistered address that requested before
-                    if !time_stored {
-                        for nonclient in shared_data.nonclients.lock().unwrap().iter_mut() {
-                            if nonclient.ip_address.eq(remote_ip) {
-                                nonclient.last_timer_access = now;
-                                time_stored = true;
-                            }
-                        }
-                    }
-                    // ip address has never requested timer before, create new nonclient to track
-                    if !time_stored {
-                        let new_nonclient = NonclientTimerAccess::new(remote_ip.clone(), now);
-                        shared_data.nonclients.lock().unwrap().push(new_nonclient);
-                    }
+                let requester = register_timer_access(&shared_data, &request);
+                let response = if requester.may_view_timer(&shared_data) {
+                    Response::from_data(
+                        generate_html(&shared_data, find_client(&shared_data, &request)).as_bytes(),
+                    )
+                } else {
+                    Response::from_data(generate_html_illegal_access().as_bytes())
+                };
+                response.with_header(html_content_type()).boxed()
+            }
+            url if url.starts_with(FILES_URL_PREFIX) => {
+                let requester = register_timer_access(&shared_data, &request);
+                if requester.may_view_timer(&shared_data) {
+                    serve_static_file(&shared_data, &url[FILES_URL_PREFIX.len()..])
+                } else {
+                    Response::from_data(generate_html_illegal_access().as_bytes())
+                        .with_header(html_content_type())
+                        .with_status_code(403)
+                        .boxed()
                 }
-                let mut response =
-                    if is_valid_client || shared_data.config.timer_allow_nonclient_access {
-                        Response::from_data(
-                            generate_html(
-                                shared_data.finish_time_as_unix(),
-                                shared_data.config.timer_duration_minutes,
-                                shared_data.config.timer_webpage_refresh_seconds,
-                                shared_data.config.timer_webpage_refresh_unstarted_seconds,
-                            )
-                            .as_bytes(),
-                        )
-                    } else {
-                        Response::from_data(generate_html_illegal_access().as_bytes())
-                    };
-                response.add_header(
-                    Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap(),
-                );
-                response
             }
-            _ => Response::from_string("404 Not Found").with_status_code(404),
+            url if shared_data.symlink_info.mode == TaskDescriptionMode::Http
+                && (url == "/task" || url.starts_with(TASK_URL_PREFIX)) =>
+            {
+                let requester = register_timer_access(&shared_data, &request);
+                let url_path = url.get(TASK_URL_PREFIX.len()..).unwrap_or_default();
+                serve_task_description(&shared_data, requester, url_path)
+            }
+            // uploads can take long, the timer page of the room must not wait for them
+            "/submit" => match submissions.try_send(request) {
+                Ok(()) => continue,
+                Err(TrySendError::Full(refused) | TrySendError::Disconnected(refused)) => {
+                    shared_data.log(
+                        Severity::Warning,
+                        "webserver",
+                        "too many uploads at once, refusing further ones".to_string(),
+                    );
+                    request = refused;
+                    Response::from_string("503 Too many uploads at once, please try again")
+                        .with_status_code(503)
+                        .boxed()
+                }
+            },
+            "/checkin" => handle_checkin(&shared_data, &mut request),
+            "/finish" => handle_finish(&shared_data, &request),
+            "/heartbeat" => {
+                if register_heartbeat(&shared_data, &request) {
+                    Response::empty(204).boxed()
+                } else {
+                    Response::empty(403).boxed()
+                }
+            }
+            _ => not_found(),
         };
 
-        // Send the response to the client
-        request.respond(response).unwrap();
+        respond(&shared_data, request, response);
+    }
+}
+
+/// Threads that receive the uploads queued into the returned sender.
+fn start_submission_workers(shared_data: &Arc<SharedData>) -> SyncSender<Request> {
+    let (sender, receiver) = mpsc::sync_channel::<Request>(SUBMISSION_QUEUE_LENGTH);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..SUBMISSION_WORKERS {
+        let shared_data = shared_data.clone();
+        let receiver = receiver.clone();
+        thread::spawn(move || loop {
+            let Ok(mut request) = receiver.lock().unwrap().recv() else {
+                return;
+            };
+            let response = handle_submission(&shared_data, &mut request);
+            respond(&shared_data, request, response);
+        });
     }
+    sender
+}
+
+/// Sends the response to the client, failures end up in the event log.
+fn respond(shared_data: &SharedData, request: Request, response: ResponseBox) {
+    let url = request.url().to_string();
+    if let Err(err) = request.respond(response) {
+        shared_data.log(
+            Severity::Warning,
+            "webserver",
+            format!("could not respond to {}: {}", url, err),
+        );
+    }
+}
+
+/// Who sent a request to the timer webserver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Requester {
+    Client,
+    /// allow-listed address like a supervisor laptop
+    Supervisor,
+    Nonclient,
+}
+
+impl Requester {
+    fn may_view_timer(&self, shared_data: &SharedData) -> bool {
+        match self {
+            Requester::Client | Requester::Supervisor => true,
+            Requester::Nonclient => shared_data.config.timer_allow_nonclient_access,
+        }
+    }
+}
+
+/// Stores the access time of the requesting address and classifies it.
+fn register_timer_access(shared_data: &SharedData, request: &Request) -> Requester {
+    let Some(std::net::SocketAddr::V4(sockaddr)) = request.remote_addr() else {
+        return Requester::Nonclient;
+    };
+    let remote_ip = sockaddr.ip();
+    let now = Utc::now();
+    // check if request comes from registered client
+    let mut is_valid_client = false;
+    for client in &shared_data.clients {
+        if client.ip_address.eq(remote_ip) {
+            *client.last_timer_access.lock().unwrap() = Some(now);
+            is_valid_client = true;
+        }
+    }
+    if is_valid_client {
+        return Requester::Client;
+    }
+    if shared_data
+        .config
+        .timer_nonclient_allowlist
+        .contains(remote_ip)
+    {
+        return Requester::Supervisor;
+    }
+    if shared_data
+        .config
+        .timer_nonclient_ignorelist
+        .contains(remote_ip)
+    {
+        return Requester::Nonclient;
+    }
+
+    let user_agent = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("User-Agent"))
+        .map(|header| header.value.to_string());
+    let path = request.url().to_string();
+    let mut nonclients = shared_data.nonclients.lock().unwrap();
+    // check if request comes from unregistered address that requested before
+    match nonclients
+        .iter_mut()
+        .find(|nonclient| nonclient.ip_address.eq(remote_ip))
+    {
+        Some(nonclient) => nonclient.register_hit(now, user_agent, path),
+        // ip address has never requested timer before, create new nonclient to track
+        None => nonclients.push(NonclientTimerAccess::new(*remote_ip, now, user_agent, path)),
+    }
+    Requester::Nonclient
+}
+
+/// Stores the heartbeat time if the request comes from a registered client.
+fn register_heartbeat(shared_data: &SharedData, request: &Request) -> bool {
+    let Some(std::net::SocketAddr::V4(sockaddr)) = request.remote_addr() else {
+        return false;
+    };
+    let now = Utc::now();
+    let mut is_valid_client = false;
+    for client in &shared_data.clients {
+        if client.ip_address.eq(sockaddr.ip()) {
+            *client.last_heartbeat.lock().unwrap() = Some(now);
+            is_valid_client = true;
+        }
+    }
+    is_valid_client
+}
+
+/// The registered client that sent the request, if any.
+fn find_client<'a>(shared_data: &'a SharedData, request: &Request) -> Option<&'a Client> {
+    match request.remote_addr() {
+        Some(std::net::SocketAddr::V4(sockaddr)) => shared_data
+            .clients
+            .iter()
+            .find(|client| client.ip_address.eq(sockaddr.ip())),
+        _ => None,
+    }
+}
+
+fn handle_checkin(shared_data: &SharedData, request: &mut Request) -> ResponseBox {
+    if !shared_data.config.timer_checkin_enabled {
+        return not_found();
+    }
+    let Some(client) = find_client(shared_data, request) else {
+        // only the seats themselves may check in, everybody else is tracked
+        register_timer_access(shared_data, request);
+        return Response::from_data(generate_html_illegal_access().as_bytes())
+            .with_header(html_content_type())
+            .with_status_code(403)
+            .boxed();
+    };
+
+    let message = if *request.method() == Method::Post {
+        match receive_checkin(shared_data, client, request) {
+            Ok(message) => format!("<p class=\"success\">{}</p>", message),
+            Err(message) => format!("<p class=\"error\">{}</p>", message),
+        }
+    } else {
+        String::new()
+    };
+    Response::from_data(generate_html_checkin(client, &message).as_bytes())
+        .with_header(html_content_type())
+        .boxed()
 }
 
-fn generate_html(
-    times: Option<i64>,
-    default_time: i64,
-    refresh_interval_running: u32,
-    refresh_interval_unstarted: u32,
-) -> String {
+/// Seats the student with the posted matriculation number at `client`.
+fn receive_checkin(
+    shared_data: &SharedData,
+    client: &Client,
+    request: &mut Request,
+) -> Result<String, String> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(4096)
+        .read_to_string(&mut body)
+        .map_err(|_| "The form was malformed.".to_string())?;
+    let matriculation_number = body
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "matriculation_number")
+        .and_then(|(_, value)| percent_decode(&value.replace('+', " ")))
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default();
+    if matriculation_number.is_empty() {
+        return Err("Please enter your matriculation number.".to_string());
+    }
+
+    let roster = shared_data.roster.lock().unwrap().clone();
+    let Some(student) = find_student(&roster, &matriculation_number) else {
+        return Err("This matriculation number is not registered for the exam.".to_string());
+    };
+    if let Some(seated) = client.student.lock().unwrap().as_ref() {
+        if *seated != student {
+            return Err(
+                "Another student is checked in at this seat, please ask the supervisor."
+                    .to_string(),
+            );
+        }
+    }
+    // students are moved between seats by the supervisor only
+    let seated_elsewhere = shared_data.clients.iter().any(|other| {
+        other.name != client.name && other.student.lock().unwrap().as_ref() == Some(&student)
+    });
+    if seated_elsewhere {
+        return Err(
+            "You are already checked in at another seat, please ask the supervisor.".to_string(),
+        );
+    }
+    let message = format!("Checked in as {}.", escape_html(&student.display()));
+    shared_data.assign_student(client, Some(student));
+    Ok(message)
+}
+
+/// Confirmation button of the countdown page, the student hands in early.
+fn handle_finish(shared_data: &Arc<SharedData>, request: &Request) -> ResponseBox {
+    let Some(client) = find_client(shared_data, request) else {
+        register_timer_access(shared_data, request);
+        return Response::from_data(generate_html_illegal_access().as_bytes())
+            .with_header(html_content_type())
+            .with_status_code(403)
+            .boxed();
+    };
+    let time_left = shared_data
+        .finish_time_of_client(client)
+        .is_some_and(|finish_time| Utc::now() < finish_time);
+    if *request.method() == Method::Post && time_left {
+        shared_data.finish_client(client);
+    }
+    // back to the countdown page, which shows the finished state
+    Response::empty(303)
+        .with_header(Header::from_bytes(&b"Location"[..], &b"/"[..]).unwrap())
+        .boxed()
+}
+
+fn handle_submission(shared_data: &SharedData, request: &mut Request) -> ResponseBox {
+    let Some(directory) = shared_data.config.submission_directory.as_ref() else {
+        return not_found();
+    };
+    let Some(client) = find_client(shared_data, request) else {
+        // uploads are only accepted from registered clients, everybody else is tracked
+        register_timer_access(shared_data, request);
+        return Response::from_data(generate_html_illegal_access().as_bytes())
+            .with_header(html_content_type())
+            .with_status_code(403)
+            .boxed();
+    };
+
+    let message = if *request.method() == Method::Post {
+        match receive_submission(shared_data, client, directory, request) {
+            Ok(message) => message,
+            Err(message) => format!("<p class=\"error\">{}</p>", message),
+        }
+    } else {
+        String::new()
+    };
+    Response::from_data(generate_html_submission(client, &message).as_bytes())
+        .with_header(html_content_type())
+        .boxed()
+}
+
+/// Stores the files of a multipart upload, returns a message for the student either way.
+fn receive_submission(
+    shared_data: &SharedData,
+    client: &Client,
+    directory: &str,
+    request: &mut Request,
+) -> Result<String, String> {
+    let Some(finish_time) = shared_data.finish_time_of_client(client) else {
+        return Err("The exam has not started yet.".to_string());
+    };
+    if Utc::now() > finish_time + Duration::minutes(shared_data.config.submission_grace_minutes) {
+        return Err("The submission period is over.".to_string());
+    }
+
+    let boundary = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Content-Type"))
+        .and_then(|header| get_multipart_boundary(header.value.as_str()))
+        .ok_or("The upload was malformed.".to_string())?;
+    let max_size = shared_data.config.submission_max_size_mb * 1024 * 1024;
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(max_size + 1)
+        .read_to_end(&mut body)
+        .map_err(|_| "The upload was interrupted.".to_string())?;
+    if body.len() as u64 > max_size {
+        return Err(format!(
+            "The upload is larger than {}MB.",
+            shared_data.config.submission_max_size_mb
+        ));
+    }
+
+    let files = parse_multipart_files(&body, &boundary);
+    if files.is_empty() {
+        return Err("No file was selected.".to_string());
+    }
+    let mut message = String::new();
+    for file in files {
+        match store_submission(directory, client, &file) {
+            Ok(submission) => {
+                message.push_str(&format!(
+                    "<p class=\"success\">Received {} ({} bytes, SHA-256 {})</p>",
+                    escape_html(&submission.file_name),
+                    submission.size,
+                    submission.sha256
+                ));
+                shared_data.record_event(
+                    RecordCategory::Submission,
+                    format!(
+                        "{} submitted '{}' ({} bytes, SHA-256 {})",
+                        client.name, submission.file_name, submission.size, submission.sha256
+                    ),
+                );
+                client.submissions.lock().unwrap().push(submission);
+                persist_session(shared_data);
+            }
+            Err(err) => {
+                shared_data.log(
+                    Severity::Error,
+                    "webserver",
+                    format!(
+                        "could not store '{}' submitted by {}: {}",
+                        file.file_name, client.name, err
+                    ),
+                );
+                message.push_str(&format!(
+                    "<p class=\"error\">Could not store {}, please ask the supervisor.</p>",
+                    escape_html(&file.file_name)
+                ));
+            }
+        }
+    }
+    Ok(message)
+}
+
+fn serve_static_file(shared_data: &SharedData, url_path: &str) -> ResponseBox {
+    let Some(root) = shared_data.config.timer_files_directory.as_ref() else {
+        return not_found();
+    };
+    if !files_available(shared_data) {
+        return Response::from_string("403 Files are not available at the moment")
+            .with_status_code(403)
+            .boxed();
+    }
+    let Some(path) = resolve_path(Path::new(root), url_path) else {
+        return not_found();
+    };
+    match File::open(&path) {
+        Ok(file) => Response::from_file(file)
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], content_type(&path).as_bytes()).unwrap(),
+            )
+            .boxed(),
+        Err(_) => not_found(),
+    }
+}
+
+/// Serves the real task description only to registered clients while it is the current
+/// target, e.g. after the start or during the reading time, the dummy otherwise.
+fn serve_task_description(
+    shared_data: &SharedData,
+    requester: Requester,
+    url_path: &str,
+) -> ResponseBox {
+    let symlink_info = &shared_data.symlink_info;
+    let real_unlocked = shared_data.symlink_target.lock().unwrap().as_deref()
+        == Some(symlink_info.real_target.as_str());
+    let target = if requester == Requester::Client && real_unlocked {
+        &symlink_info.real_target
+    } else {
+        &symlink_info.dummy_target
+    };
+
+    let target = Path::new(target);
+    // a single file is served for every path, a directory like the /files/ directory
+    let path = if target.is_file() {
+        Some(target.to_path_buf())
+    } else {
+        resolve_path(target, url_path)
+    };
+    match path.and_then(|path| File::open(&path).ok().map(|file| (path, file))) {
+        Some((path, file)) => Response::from_file(file)
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], content_type(&path).as_bytes()).unwrap(),
+            )
+            .with_header(Header::from_bytes(&b"Cache-Control"[..], &b"no-store"[..]).unwrap())
+            .boxed(),
+        None => not_found(),
+    }
+}
+
+fn files_available(shared_data: &SharedData) -> bool {
+    match shared_data.config.timer_files_availability {
+        FilesAvailability::Always => true,
+        FilesAvailability::AfterStart => shared_data.times.lock().unwrap().is_some(),
+        FilesAvailability::WhileRunning => shared_data
+            .finish_time_as_unix()
+            .is_some_and(|finish_time| Utc::now().timestamp() < finish_time),
+    }
+}
+
+fn html_content_type() -> Header {
+    Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap()
+}
+
+fn not_found() -> ResponseBox {
+    Response::from_string("404 Not Found")
+        .with_status_code(404)
+        .boxed()
+}
+
+fn generate_html(shared_data: &SharedData, client: Option<&Client>) -> String {
+    let config = &shared_data.config;
+    let times = match client {
+        Some(client) => shared_data
+            .finish_time_of_client(client)
+            .map(|finish_time| finish_time.timestamp()),
+        None => shared_data.finish_time_as_unix(),
+    };
     let target_time = times.unwrap_or(-1);
+    let scheduled_start = shared_data
+        .scheduled_start
+        .lock()
+        .unwrap()
+        .map(|start| start.timestamp())
+        .unwrap_or(-1);
+    let mut links = String::new();
+    let finished = client.and_then(|client| *client.finished.lock().unwrap());
+    if let Some(finished) = finished {
+        links.push_str(&format!(
+            r#"<div id="finished">You finished the exam at {}</div>"#,
+            finished.with_timezone(&Local).format("%H:%M")
+        ));
+    } else if client.is_some() && times.is_some_and(|finish| Utc::now().timestamp() < finish) {
+        links.push_str(
+            r#"<form class="link" method="post" action="/finish" onsubmit="return confirm('Do you really want to finish the exam? Your keyboard will be locked.')">
+                    <input type="submit" value="I am finished">
+                </form>"#,
+        );
+    }
+    if let Some(client) = client {
+        if let Some(student) = client.student.lock().unwrap().as_ref() {
+            links.push_str(&format!(
+                r#"<div id="student">{}</div>"#,
+                escape_html(&student.display())
+            ));
+        } else if config.timer_checkin_enabled {
+            links.push_str(r#"<div class="link"><a href="/checkin">Check in</a></div>"#);
+        }
+    }
+    if config.submission_directory.is_some() {
+        links.push_str(r#"<div class="link"><a href="/submit">Hand in files</a></div>"#);
+    }
+    let phase_end = shared_data
+        .phase_end()
+        .map(|phase_end| phase_end.timestamp())
+        .unwrap_or(-1);
+    let phase_name = shared_data
+        .current_phase()
+        .map(|phase| serde_json::to_string(&phase.name).unwrap_or_default())
+        .unwrap_or("\"\"".to_string());
+    let phase_text = shared_data
+        .current_phase()
+        .and_then(|phase| phase.webpage_text.as_deref())
+        .map(escape_html)
+        .unwrap_or_default();
+    let milestones_json =
+        serde_json::to_string(&config.timer_warning_milestones).unwrap_or("[]".to_string());
     let refresh_delay = match times {
-        Some(_) => refresh_interval_running,
-        None => refresh_interval_unstarted,
+        Some(_) => config.timer_webpage_refresh_seconds,
+        None => config.timer_webpage_refresh_unstarted_seconds,
     };
     format!(
-        r#"
+        r##"
         <!DOCTYPE html>
         <html lang="en">
         <head>
@@ -102,17 +600,103 @@ fn generate_html(
                     background-color: #f0f0f0;
                     margin: 0;
                 }}
+                #container {{
+                    text-align: center;
+                }}
                 #countdown {{
                     font-size: 48px;
                     color: #333;
                 }}
+                #banner {{
+                    font-size: 32px;
+                    font-weight: bold;
+                    color: #333;
+                    margin-top: 16px;
+                }}
+                #phase {{
+                    font-size: 28px;
+                    color: #333;
+                    margin-top: 16px;
+                }}
+                #finished {{
+                    font-size: 28px;
+                    font-weight: bold;
+                    color: #2e7d32;
+                    margin-top: 32px;
+                }}
+                #student {{
+                    font-size: 24px;
+                    color: #333;
+                    margin-top: 32px;
+                }}
+                .link {{
+                    font-size: 20px;
+                    margin-top: 32px;
+                }}
             </style>
         </head>
         <body>
-            <div id="countdown"></div>
+            <div id="container">
+                <div id="countdown"></div>
+                <div id="banner"></div>
+                <div id="phase">{}</div>
+                {}
+            </div>
 
             <script>
                 const targetDate = {};
+                const finished = {};
+                const startDate = {};
+                const phaseEnd = {};
+                const phaseName = {};
+                const milestones = {};
+                const heartbeatInterval = {};
+
+                // tell the manager that this page is still open, independent of page reloads
+                function sendHeartbeat() {{
+                    fetch("/heartbeat", {{ method: "POST", cache: "no-store" }}).catch(() => {{}});
+                }}
+                sendHeartbeat();
+                setInterval(sendHeartbeat, heartbeatInterval * 1000);
+
+                // pick up the next state as soon as the scheduled start or the end of the phase is reached
+                const upcoming = [startDate, phaseEnd].filter(date => date != -1);
+                if (targetDate == -1 && upcoming.length > 0) {{
+                    const untilChange = Math.min(...upcoming) - new Date().getTime() / 1000;
+                    setTimeout(() => location.reload(), Math.max(untilChange + 2, 1) * 1000);
+                }}
+
+                // the page reloads regularly, remember which milestones already played their sound
+                function playSoundOnce(milestone) {{
+                    const key = "milestone_" + targetDate + "_" + milestone.minutes_left;
+                    if (sessionStorage.getItem(key) !== null) {{
+                        return;
+                    }}
+                    sessionStorage.setItem(key, "played");
+                    try {{
+                        const audio = new AudioContext();
+                        const oscillator = audio.createOscillator();
+                        oscillator.frequency.value = 880;
+                        oscillator.connect(audio.destination);
+                        oscillator.start();
+                        oscillator.stop(audio.currentTime + 0.5);
+                    }} catch (e) {{}}
+                }}
+
+                function activeMilestone(distance) {{
+                    // the time is up, no more warnings
+                    if (distance <= 0) {{
+                        return null;
+                    }}
+                    let active = null;
+                    for (const milestone of milestones) {{
+                        if (distance <= milestone.minutes_left * 60
+                            && (active === null || milestone.minutes_left < active.minutes_left)) {{
+                            active = milestone;
+                        }}
+                    }}
+                    return active;
+                }}
 
                 // Update the countdown every 1 second
                 const countdown = setInterval(function() {{
@@ -121,8 +705,20 @@ fn generate_html(
                     const now = new Date().getTime() / 1000;
                     const distance = targetDate - now;
 
-                    if (targetDate == -1) {{
+                    if (targetDate == -1 && phaseEnd > now) {{
+                        const untilEnd = phaseEnd - now;
+                        const minutes = Math.floor(untilEnd / 60);
+                        const seconds = Math.floor((untilEnd % 60));
+                        text = phaseName + ": " + minutes + "m " + seconds + "s";
+                    }} else if (targetDate == -1 && startDate > now) {{
+                        const untilStart = startDate - now;
+                        const minutes = Math.floor(untilStart / 60);
+                        const seconds = Math.floor((untilStart % 60));
+                        text = "Exam starts in " + minutes + "m " + seconds + "s";
+                    }} else if (targetDate == -1) {{
                         text = "Time left: {}min 0s";
+                    }} else if (finished) {{
+                        text = "Finished";
                     }} else if (distance < 0) {{
                         text = "time is up!";
                     }} else {{
@@ -131,18 +727,151 @@ fn generate_html(
                         text = "Time left: " + minutes + "m " + seconds + "s";
                     }}
 
+                    let banner = "";
+                    let color = "#f0f0f0";
+                    if (targetDate != -1 && !finished) {{
+                        const milestone = activeMilestone(distance);
+                        if (milestone !== null) {{
+                            banner = milestone.banner;
+                            color = milestone.color;
+                            if (milestone.play_sound) {{
+                                playSoundOnce(milestone);
+                            }}
+                        }}
+                    }}
+
                     document.getElementById("countdown").innerHTML = text;
+                    document.getElementById("banner").innerText = banner;
+                    document.body.style.backgroundColor = color;
                     document.title = text;
 
                 }}, 1000);
             </script>
         </body>
         </html>
+        "##,
+        refresh_delay,
+        phase_text,
+        links,
+        target_time,
+        finished.is_some(),
+        scheduled_start,
+        phase_end,
+        phase_name,
+        milestones_json,
+        config.timer_heartbeat_interval_seconds,
+        client
+            .and_then(|client| shared_data.group_of_client(client))
+            .map(|group| group.duration_minutes)
+            .unwrap_or(config.timer_duration_minutes)
+    )
+}
+
+fn generate_html_checkin(client: &Client, message: &str) -> String {
+    let current = match client.student.lock().unwrap().as_ref() {
+        Some(student) => format!(
+            "<p>Checked in at this seat: {}</p>",
+            escape_html(&student.display())
+        ),
+        None => "<p>Nobody is checked in at this seat yet.</p>".to_string(),
+    };
+    format!(
+        r#"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title>Check in</title>
+            <style>
+                body {{
+                    font-family: Arial, sans-serif;
+                    background-color: #f0f0f0;
+                    margin: 40px;
+                    font-size: 20px;
+                }}
+                .success {{ color: green; }}
+                .error {{ color: red; font-weight: bold; }}
+            </style>
+        </head>
+        <body>
+            <h1>Check in at {}</h1>
+            {}
+            {}
+            <form method="post" action="/checkin">
+                <label>Matriculation number <input type="text" name="matriculation_number" autofocus></label>
+                <input type="submit" value="Check in">
+            </form>
+            <a href="/">Back to the timer</a>
+        </body>
+        </html>
+        "#,
+        escape_html(&client.name),
+        message,
+        current
+    )
+}
+
+fn generate_html_submission(client: &Client, message: &str) -> String {
+    let submissions = client
+        .submissions
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|submission| {
+            format!(
+                "<li>{} {} ({} bytes)</li>",
+                submission.time.with_timezone(&Local).format("%H:%M:%S"),
+                escape_html(&submission.file_name),
+                submission.size
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"
+        <!DOCTYPE html>
+        <html lang="en">
+        <head>
+            <meta charset="UTF-8">
+            <meta name="viewport" content="width=device-width, initial-scale=1.0">
+            <title>Hand in files</title>
+            <style>
+                body {{
+                    font-family: Arial, sans-serif;
+                    background-color: #f0f0f0;
+                    margin: 40px;
+                    font-size: 20px;
+                }}
+                .success {{ color: green; }}
+                .error {{ color: red; font-weight: bold; }}
+            </style>
+        </head>
+        <body>
+            <h1>Hand in files from {}</h1>
+            {}
+            <form method="post" action="/submit" enctype="multipart/form-data">
+                <input type="file" name="files" multiple>
+                <input type="submit" value="Upload">
+            </form>
+            <h2>Received so far</h2>
+            <ul>{}</ul>
+            <a href="/">Back to the timer</a>
+        </body>
+        </html>
         "#,
-        refresh_delay, target_time, default_time
+        escape_html(&client.name),
+        message,
+        submissions
     )
 }
 
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 fn generate_html_illegal_access() -> String {
     r#"
     <!DOCTYPE html>
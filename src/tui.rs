@@ -1,24 +1,69 @@
-use chrono::{Duration, Local, Utc};
+use chrono::{DateTime, Duration, Local, Utc};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use ratatui::{
     prelude::*,
     symbols::border,
     widgets::{block::*, *},
 };
-use std::{io, iter::repeat, sync::Arc};
+use std::{io, str::FromStr, sync::Arc};
 
 use crate::{
+    client::{Client, Note},
+    collection::{start_collection, CollectionStatus},
+    event_log::Severity,
+    exam::{
+        adjust_duration, advance_phase, parse_start_time, parse_time_of_day, schedule_start,
+        start_exam,
+    },
+    exam_record::RecordCategory,
+    export::export_exam_record,
+    input_parser::{get_active_milestone, RoomLayout, TaskDescriptionMode},
     kanata_tcp::{disable_keyboards, enable_keyboards},
-    persistance::{delete_persisted_time, persist_time},
+    persistance::{
+        delete_persisted_record, delete_persisted_session, delete_persisted_time, persist_session,
+    },
+    roster::{find_student, load_roster, search_student, Student},
     shared_data::SharedData,
+    submission::Submission,
     symlinks::{lock_taskdescription, unlock_taskdescription},
+    time_adjustment::{apply_adjustment, parse_adjustment, Adjustment},
+    tui_action::{key_name, Action, ConfirmationMode, KeyBindings, PendingAction, PendingState},
     tui_basic,
+    tui_bigdigits::{big_text_width, max_scale, render_big_text, BIG_TEXT_HEIGHT},
+    tui_client_table::{visible_clients, ClientFilter, SortColumn},
+    tui_input::{InputPrompt, InputResult},
+    tui_seatmap::render_seatmap,
+    undo::{
+        capture_layers, capture_phase, capture_task_description, capture_times, is_unchanged, undo,
+        undo_refusal, Inverse, UndoEntry,
+    },
 };
 
-pub fn tui_main(shared_data: Arc<SharedData>) -> io::Result<()> {
+/// more unacknowledged nonclients than this are summarized in the alert
+const MAX_ALERT_ENTRIES: usize = 4;
+/// only the newest notes of the selected client are shown
+const MAX_NOTE_LINES: usize = 5;
+/// older actions can not be undone anymore
+const MAX_UNDO_ENTRIES: usize = 20;
+/// entries of the expanded event log, collapsed only the newest is shown
+const EVENT_LOG_LINES: usize = 10;
+/// rows skipped in the client overview by PageUp and PageDown
+const PAGE_STEP: isize = 10;
+/// hex digits of the record head hash shown in the status
+const RECORD_HASH_PREFIX: usize = 16;
+
+pub fn tui_main(
+    shared_data: Arc<SharedData>,
+    room_layout: Option<RoomLayout>,
+    key_bindings: KeyBindings,
+) -> io::Result<()> {
     let mut terminal = tui_basic::init()?;
-    let app_result = App::new(shared_data).run(&mut terminal);
+    let mut app = App::new(shared_data, room_layout, key_bindings);
+    let app_result = app.run(&mut terminal);
     tui_basic::restore()?;
+    if let Some(exit_message) = app.exit_message {
+        println!("{}", exit_message);
+    }
     app_result
 }
 
@@ -26,13 +71,68 @@ pub fn tui_main(shared_data: Arc<SharedData>) -> io::Result<()> {
 pub struct App {
     exit: bool,
     shared_data: Arc<SharedData>,
+    /// feedback for the last action that has no visible effect otherwise
+    message: Option<String>,
+    /// index into `shared_data.clients` for actions concerning a single client
+    selected_client: usize,
+    input: Option<InputPrompt<InputPurpose>>,
+    room_layout: Option<RoomLayout>,
+    show_seatmap: bool,
+    /// full screen countdown instead of the panels
+    projector: bool,
+    key_bindings: KeyBindings,
+    show_help: bool,
+    event_log_expanded: bool,
+    /// actions that can be undone, newest last
+    history: Vec<UndoEntry>,
+    /// room wide action waiting for its confirmation
+    pending: Option<PendingAction>,
+    sort_column: SortColumn,
+    client_filter: ClientFilter,
+    /// scroll position of the client overview
+    client_table: TableState,
+    /// printed once the terminal is restored
+    exit_message: Option<String>,
+}
+
+/// What the text of the open input prompt is used for.
+#[derive(Debug)]
+enum InputPurpose {
+    ClientNote(usize),
+    AssignStudent(usize),
+    ImportRoster,
+    ScheduleStart,
+    AdjustDuration,
+    StartOverride(usize),
+    Group(usize),
+    /// the room name has to be typed to execute the action
+    ConfirmAction(Action),
 }
 
 impl App {
-    fn new(shared_data: Arc<SharedData>) -> Self {
+    fn new(
+        shared_data: Arc<SharedData>,
+        room_layout: Option<RoomLayout>,
+        key_bindings: KeyBindings,
+    ) -> Self {
         App {
             exit: false,
             shared_data,
+            message: None,
+            selected_client: 0,
+            input: None,
+            room_layout,
+            show_seatmap: false,
+            projector: false,
+            key_bindings,
+            show_help: false,
+            event_log_expanded: false,
+            history: Vec::new(),
+            pending: None,
+            sort_column: SortColumn::default(),
+            client_filter: ClientFilter::default(),
+            client_table: TableState::default(),
+            exit_message: None,
         }
     }
 }
@@ -48,17 +148,105 @@ impl App {
     }
 
     fn render_frame(&mut self, frame: &mut Frame) {
+        // clients change their layer, heartbeat etc. and drop out of the filter on their own
+        self.move_selection(0);
+        if self.projector {
+            render_projector(self, frame.area(), frame.buffer_mut());
+        } else {
+            self.render_panels(frame);
+        }
+        if let Some(input) = &self.input {
+            let hint = match input.purpose {
+                InputPurpose::AdjustDuration => Some(adjustment_hint(self, &input.text)),
+                InputPurpose::StartOverride(client_index) => {
+                    Some(start_override_hint(self, client_index, &input.text))
+                }
+                InputPurpose::Group(_) => Some(Line::from(format!(
+                    "Groups: {}",
+                    self.shared_data
+                        .groups
+                        .iter()
+                        .map(|group| format!("{} ({}min)", group.name, group.duration_minutes))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))),
+                InputPurpose::AssignStudent(client_index) => {
+                    Some(student_hint(self, client_index, &input.text))
+                }
+                _ => None,
+            };
+            input.render(hint, frame.area(), frame.buffer_mut());
+        }
+        if let Some(pending) = &self.pending {
+            pending.render(frame.area(), frame.buffer_mut());
+        }
+        if self.show_help {
+            self.key_bindings
+                .render_help(frame.area(), frame.buffer_mut());
+        }
+    }
+
+    fn render_panels(&mut self, frame: &mut Frame) {
+        let alert_count = self
+            .shared_data
+            .nonclients
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|nonclient| nonclient.is_unacknowledged())
+            .count();
+        let alert_height = match alert_count {
+            0 => 0,
+            count => 2 + 2 * count.min(MAX_ALERT_ENTRIES) as u16,
+        };
+        let note_count = self
+            .selected_visible_client()
+            .map(|index| self.shared_data.clients[index].notes.lock().unwrap().len())
+            .unwrap_or(0);
+        let notes_height = match note_count {
+            0 => 0,
+            count => 2 + count.min(MAX_NOTE_LINES) as u16,
+        };
+        let event_log_height = if self.event_log_expanded {
+            2 + EVENT_LOG_LINES as u16
+        } else {
+            3
+        };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
+                Constraint::Length(status_height(self)),
                 Constraint::Length(3),
-                Constraint::Length(3),
+                Constraint::Length(alert_height),
                 Constraint::Min(5),
+                Constraint::Length(event_log_height),
+                Constraint::Length(notes_height),
             ])
             .split(frame.area());
         render_status(self, chunks[0], frame.buffer_mut());
         render_symlinks(self, chunks[1], frame.buffer_mut());
-        render_clients(self, chunks[2], frame.buffer_mut());
+        if alert_count > 0 {
+            render_nonclient_alert(self, chunks[2], frame.buffer_mut());
+        }
+        match &self.room_layout {
+            Some(room_layout) if self.show_seatmap => render_seatmap(
+                &self.shared_data,
+                room_layout,
+                self.selected_visible_client(),
+                self.key_hint(Action::ToggleSeatmap),
+                chunks[3],
+                frame.buffer_mut(),
+            ),
+            _ => {
+                let mut client_table = std::mem::take(&mut self.client_table);
+                render_clients(self, &mut client_table, chunks[3], frame.buffer_mut());
+                self.client_table = client_table;
+            }
+        }
+        render_event_log(self, chunks[4], frame.buffer_mut());
+        if note_count > 0 {
+            render_notes(self, chunks[5], frame.buffer_mut());
+        }
     }
 
     /// updates the application's state based on user input
@@ -73,80 +261,521 @@ impl App {
                 _ => {}
             };
         }
+        if let Some(pending) = &mut self.pending {
+            let state = pending.tick();
+            self.resolve_pending(state);
+        }
         Ok(())
     }
 
     fn handle_key_event(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Char('q') => {
-                delete_persisted_time();
-                self.exit();
-            }
-            KeyCode::Enter => {
-                let mut times = self.shared_data.times.lock().unwrap();
-                // no times stored => timer has not yet been started
-                if times.is_none() {
-                    let now = Utc::now();
-                    let duration =
-                        Duration::minutes(self.shared_data.config.timer_duration_minutes);
-                    *times = Some((now, duration));
-
-                    unlock_taskdescription(self.shared_data.clone());
-                    persist_time(now, duration);
+        // an open prompt captures all keys until it is confirmed or cancelled
+        if let Some(input) = &mut self.input {
+            match input.handle_key_event(key_event) {
+                InputResult::Editing => {}
+                InputResult::Submitted(text) => {
+                    let input = self.input.take().expect("checked above");
+                    self.submit_input(input.purpose, text);
                 }
+                InputResult::Cancelled => self.input = None,
             }
-            KeyCode::Char('+') => {
-                let mut times = self.shared_data.times.lock().unwrap();
-                if times.is_some() {
-                    let (start_time, duration) = times.unwrap();
-                    let new_duration = duration + Duration::minutes(1);
-                    *times = Some((start_time, new_duration));
-                    persist_time(start_time, new_duration);
+            return;
+        }
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+        // keys answer the pending action, unless it is already over
+        if let Some(pending) = &mut self.pending {
+            let state = pending.handle_key(key_event.code);
+            let finished = matches!(state, PendingState::Finished);
+            self.resolve_pending(state);
+            if !finished {
+                return;
+            }
+        }
+        match key_event.code {
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-PAGE_STEP),
+            KeyCode::PageDown => self.move_selection(PAGE_STEP),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            key => {
+                if let Some(action) = self.key_bindings.action(key) {
+                    self.request(action, key);
                 }
             }
-            KeyCode::Char('-') => {
-                let mut times = self.shared_data.times.lock().unwrap();
-                if times.is_some() {
-                    let (start_time, duration) = times.unwrap();
-                    let new_duration = duration - Duration::minutes(1);
-                    *times = Some((start_time, new_duration));
-                    persist_time(start_time, new_duration);
+        }
+    }
+
+    /// Executes `action` right away or once it is confirmed as configured.
+    fn request(&mut self, action: Action, key: KeyCode) {
+        let config = &self.shared_data.config;
+        match config
+            .tui_confirmations
+            .get(&action)
+            .copied()
+            .unwrap_or_default()
+        {
+            ConfirmationMode::None => self.perform(action),
+            ConfirmationMode::TypeRoomName => {
+                self.input = Some(InputPrompt::new(
+                    InputPurpose::ConfirmAction(action),
+                    format!(
+                        "Type '{}' to {}",
+                        self.shared_data.room,
+                        action.description()
+                    ),
+                ))
+            }
+            mode => {
+                let duration = std::time::Duration::from_millis(config.tui_confirmation_ms);
+                self.pending = Some(PendingAction::new(action, key, mode, duration));
+            }
+        }
+    }
+
+    fn resolve_pending(&mut self, state: PendingState) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        let action = pending.action;
+        match state {
+            PendingState::Waiting => {}
+            PendingState::Confirmed => {
+                // a hold stays pending until the key is released, so the repeats are ignored
+                if pending.mode != ConfirmationMode::Hold {
+                    self.pending = None;
                 }
+                self.perform(action);
             }
-            KeyCode::Char(' ') => {
+            PendingState::Aborted => {
+                self.pending = None;
+                self.message = Some(format!("Aborted: {}", action.description()));
+            }
+            PendingState::Finished => self.pending = None,
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::DisableKeyboards => {
+                let inverse = capture_layers(&self.shared_data);
                 disable_keyboards(self.shared_data.clone());
+                self.remember("disable keyboards", vec![inverse]);
             }
-            KeyCode::Esc => {
+            Action::EnableKeyboards => {
+                let inverse = capture_layers(&self.shared_data);
                 enable_keyboards(self.shared_data.clone());
+                self.remember("enable keyboards", vec![inverse]);
             }
-            KeyCode::Char('d') => {
+            Action::TaskDescriptionDummy => {
+                let inverse = capture_task_description(&self.shared_data);
                 lock_taskdescription(self.shared_data.clone());
+                self.remember("task description set to dummy", vec![inverse]);
             }
-            KeyCode::Char('r') => {
+            Action::TaskDescriptionReal => {
+                let inverse = capture_task_description(&self.shared_data);
                 unlock_taskdescription(self.shared_data.clone());
+                self.remember("task description set to real", vec![inverse]);
+            }
+            Action::NextPhase => {
+                let inverses = vec![
+                    capture_phase(&self.shared_data),
+                    capture_times(&self.shared_data),
+                    capture_task_description(&self.shared_data),
+                    capture_layers(&self.shared_data),
+                ];
+                advance_phase(self.shared_data.clone());
+                self.remember("next phase", inverses);
+            }
+            Action::CollectWork => {
+                start_collection(self.shared_data.clone());
+            }
+            Action::Quit => {
+                // the record is deleted with the session, keep a copy if anything happened
+                if !self.shared_data.record.lock().unwrap().entries.is_empty() {
+                    match export_exam_record(&self.shared_data) {
+                        Ok(path) => {
+                            let head_hash = self
+                                .shared_data
+                                .record
+                                .lock()
+                                .unwrap()
+                                .head_hash()
+                                .to_string();
+                            self.exit_message = Some(format!(
+                                "Exam record exported to {}, head hash {}",
+                                path.display(),
+                                head_hash
+                            ));
+                        }
+                        Err(err) => {
+                            self.message =
+                                Some(format!("Export failed, the session is kept: {}", err));
+                            return;
+                        }
+                    }
+                }
+                delete_persisted_time();
+                delete_persisted_record();
+                delete_persisted_session();
+                self.exit();
+            }
+            Action::Help => self.show_help = true,
+            Action::ToggleEventLog => self.event_log_expanded = !self.event_log_expanded,
+            Action::SortNext => self.sort_column = self.sort_column.next(),
+            Action::SortPrevious => self.sort_column = self.sort_column.previous(),
+            Action::CycleFilter => {
+                self.client_filter = self.client_filter.next();
+                // keep a visible client selected
+                self.move_selection(0);
+            }
+            Action::ToggleProjector => self.projector = !self.projector,
+            Action::ToggleSeatmap => {
+                self.show_seatmap = !self.show_seatmap && self.room_layout.is_some();
+            }
+            Action::AddNote => {
+                if let Some(index) = self.selected_visible_client() {
+                    let client = &self.shared_data.clients[index];
+                    self.input = Some(InputPrompt::new(
+                        InputPurpose::ClientNote(index),
+                        format!("Note for {}", client.name),
+                    ));
+                }
+            }
+            Action::AssignStudent => {
+                if let Some(index) = self.selected_visible_client() {
+                    let client = &self.shared_data.clients[index];
+                    self.input = Some(InputPrompt::new(
+                        InputPurpose::AssignStudent(index),
+                        format!("Student at {} (empty frees the seat)", client.name),
+                    ));
+                }
+            }
+            Action::ImportRoster => {
+                self.input = Some(InputPrompt::with_text(
+                    InputPurpose::ImportRoster,
+                    "Import roster CSV".to_string(),
+                    self.shared_data
+                        .config
+                        .roster_path
+                        .clone()
+                        .unwrap_or_default(),
+                ));
+            }
+            Action::StartOverride if self.shared_data.times.lock().unwrap().is_some() => {
+                if let Some(index) = self.selected_visible_client() {
+                    let client = &self.shared_data.clients[index];
+                    self.input = Some(InputPrompt::new(
+                        InputPurpose::StartOverride(index),
+                        format!(
+                            "Individual start of {}: HH:MM or now (empty resets)",
+                            client.name
+                        ),
+                    ));
+                }
+            }
+            Action::ToggleFinished => {
+                if let Some(index) = self.selected_visible_client() {
+                    let client = &self.shared_data.clients[index];
+                    if client.finished.lock().unwrap().is_some() {
+                        self.shared_data.reopen_client(client);
+                    } else if !self.shared_data.finish_client(client) {
+                        self.message = Some(format!(
+                            "{} cannot finish before the exam has started",
+                            client.name
+                        ));
+                    }
+                }
+            }
+            Action::SetGroup if !self.shared_data.groups.is_empty() => {
+                if let Some(index) = self.selected_visible_client() {
+                    let client = &self.shared_data.clients[index];
+                    self.input = Some(InputPrompt::new(
+                        InputPurpose::Group(index),
+                        format!("Group of {} (empty for the default duration)", client.name),
+                    ));
+                }
             }
+            Action::Undo => match self.history.pop() {
+                Some(entry) => match undo_refusal(&self.shared_data, &entry) {
+                    Some(reason) => {
+                        self.message =
+                            Some(format!("Cannot undo '{}': {}", entry.description, reason));
+                    }
+                    None => {
+                        self.message = Some(format!("Undone: {}", entry.description));
+                        undo(self.shared_data.clone(), entry);
+                    }
+                },
+                None => self.message = Some("Nothing to undo".to_string()),
+            },
+            Action::StartExam => {
+                let inverses = vec![
+                    capture_times(&self.shared_data),
+                    capture_task_description(&self.shared_data),
+                    capture_phase(&self.shared_data),
+                    capture_layers(&self.shared_data),
+                ];
+                let started = start_exam(self.shared_data.clone());
+                if started {
+                    self.remember("exam start", inverses);
+                }
+                // starting by hand makes a planned start obsolete
+                if started && self.shared_data.scheduled_start.lock().unwrap().is_some() {
+                    schedule_start(&self.shared_data, None);
+                }
+            }
+            Action::ScheduleStart if self.shared_data.times.lock().unwrap().is_none() => {
+                self.input = Some(InputPrompt::new(
+                    InputPurpose::ScheduleStart,
+                    "Scheduled start HH:MM (empty cancels)".to_string(),
+                ));
+            }
+            Action::AddMinute => {
+                let inverse = capture_times(&self.shared_data);
+                let adjustment = Adjustment::Relative(Duration::minutes(1));
+                if adjust_duration(&self.shared_data, adjustment).is_ok() {
+                    self.remember("+1min", vec![inverse]);
+                }
+            }
+            Action::SubtractMinute => {
+                let inverse = capture_times(&self.shared_data);
+                let adjustment = Adjustment::Relative(Duration::minutes(-1));
+                if adjust_duration(&self.shared_data, adjustment).is_ok() {
+                    self.remember("-1min", vec![inverse]);
+                }
+            }
+            Action::AdjustDuration if self.shared_data.times.lock().unwrap().is_some() => {
+                self.input = Some(InputPrompt::new(
+                    InputPurpose::AdjustDuration,
+                    "Adjust duration: +15, -5, =120 or until 12:30".to_string(),
+                ));
+            }
+            Action::ExportRecord => {
+                self.message = Some(match export_exam_record(&self.shared_data) {
+                    Ok(path) => format!(
+                        "Exported {}",
+                        path.file_name().unwrap_or_default().to_string_lossy()
+                    ),
+                    Err(err) => format!("Export failed: {}", err),
+                });
+            }
+            Action::AcknowledgeNonclients => {
+                for nonclient in self.shared_data.nonclients.lock().unwrap().iter_mut() {
+                    nonclient.acknowledge();
+                }
+            }
+            // actions that are not possible in the current state
             _ => {}
         }
     }
 
+    fn submit_input(&mut self, purpose: InputPurpose, text: String) {
+        match purpose {
+            InputPurpose::ConfirmAction(action) => {
+                if text == self.shared_data.room {
+                    self.perform(action);
+                } else {
+                    self.message = Some(format!("Aborted: {}", action.description()));
+                }
+            }
+            InputPurpose::ClientNote(client_index) => {
+                let Some(client) = self.shared_data.clients.get(client_index) else {
+                    return;
+                };
+                if text.is_empty() {
+                    return;
+                }
+                client.notes.lock().unwrap().push(Note {
+                    time: Utc::now(),
+                    text: text.clone(),
+                });
+                self.shared_data
+                    .record_event(RecordCategory::Note, format!("{}: {}", client.name, text));
+                persist_session(&self.shared_data);
+            }
+            InputPurpose::AssignStudent(client_index) => {
+                let Some(client) = self.shared_data.clients.get(client_index) else {
+                    return;
+                };
+                if text.is_empty() {
+                    self.shared_data.assign_student(client, None);
+                    return;
+                }
+                let roster = self.shared_data.roster.lock().unwrap().clone();
+                let student = if roster.is_empty() {
+                    // without roster the input is taken as matriculation number
+                    find_student(&roster, &text)
+                } else {
+                    search_student(&roster, &text)
+                };
+                match student {
+                    Some(student) => self.shared_data.assign_student(client, Some(student)),
+                    None => self.message = Some(format!("No unique student matches '{}'", text)),
+                }
+            }
+            InputPurpose::StartOverride(client_index) => {
+                let Some(client) = self.shared_data.clients.get(client_index) else {
+                    return;
+                };
+                match parse_start_override(&text) {
+                    Ok(start) => self.shared_data.set_start_override(client, start),
+                    Err(err) => self.message = Some(err),
+                }
+            }
+            InputPurpose::Group(client_index) => {
+                let Some(client) = self.shared_data.clients.get(client_index) else {
+                    return;
+                };
+                if text.is_empty() {
+                    self.shared_data.set_group(client, None);
+                } else if self
+                    .shared_data
+                    .groups
+                    .iter()
+                    .any(|group| group.name == text)
+                {
+                    self.shared_data.set_group(client, Some(text));
+                } else {
+                    self.message = Some(format!("There is no group '{}'", text));
+                }
+            }
+            InputPurpose::AdjustDuration => {
+                let inverse = capture_times(&self.shared_data);
+                let result = parse_adjustment(&text)
+                    .and_then(|adjustment| adjust_duration(&self.shared_data, adjustment));
+                match result {
+                    Ok(()) => self.remember(&format!("duration {}", text), vec![inverse]),
+                    Err(err) => self.message = Some(format!("Duration unchanged: {}", err)),
+                }
+            }
+            InputPurpose::ScheduleStart => {
+                if text.is_empty() {
+                    if self.shared_data.scheduled_start.lock().unwrap().is_some() {
+                        schedule_start(&self.shared_data, None);
+                    }
+                    return;
+                }
+                match parse_start_time(&text) {
+                    Ok(start) => schedule_start(&self.shared_data, Some(start)),
+                    Err(err) => self.message = Some(err),
+                }
+            }
+            InputPurpose::ImportRoster => {
+                self.message = Some(match load_roster(&text) {
+                    Ok(roster) => {
+                        let message = format!("Imported {} students", roster.len());
+                        self.shared_data.record_event(
+                            RecordCategory::Student,
+                            format!(
+                                "roster with {} students imported from {}",
+                                roster.len(),
+                                text
+                            ),
+                        );
+                        *self.shared_data.roster.lock().unwrap() = roster;
+                        message
+                    }
+                    Err(err) => format!("Roster import failed: {}", err),
+                });
+            }
+        }
+    }
+
+    /// Moves the selection by `step` rows of the client overview, the first visible
+    /// client is selected if the selected one is filtered out.
+    fn move_selection(&mut self, step: isize) {
+        let visible = visible_clients(&self.shared_data, self.sort_column, self.client_filter);
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let position = match visible
+            .iter()
+            .position(|&index| index == self.selected_client)
+        {
+            Some(position) => position.saturating_add_signed(step).min(last),
+            None => 0,
+        };
+        self.selected_client = visible[position];
+    }
+
+    /// The selected client, `None` if the filter hides every client.
+    fn selected_visible_client(&self) -> Option<usize> {
+        visible_clients(&self.shared_data, self.sort_column, self.client_filter)
+            .contains(&self.selected_client)
+            .then_some(self.selected_client)
+    }
+
+    /// Keeps `inverses` so the action `description` can be undone with `u`.
+    fn remember(&mut self, description: &str, mut inverses: Vec<Inverse>) {
+        // only what the action changed is restored, e.g. a phase without a task description
+        inverses.retain(|inverse| !is_unchanged(&self.shared_data, inverse));
+        if inverses.is_empty() {
+            return;
+        }
+        self.history.push(UndoEntry {
+            description: description.to_string(),
+            inverses,
+        });
+        if self.history.len() > MAX_UNDO_ENTRIES {
+            self.history.remove(0);
+        }
+    }
+
+    /// Key of `action` for the instructions in the block titles.
+    fn key_hint(&self, action: Action) -> Span<'static> {
+        format!("<{}> ", key_name(self.key_bindings.key(action)))
+            .blue()
+            .bold()
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
 }
 
 fn render_status(app: &App, area: Rect, buf: &mut Buffer) {
-    let title = Title::from(" Timer ".bold());
+    let title = Title::from(vec![
+        " Timer ".bold(),
+        " Projector view ".into(),
+        app.key_hint(Action::ToggleProjector),
+    ]);
+    let phase_index = *app.shared_data.phase.lock().unwrap();
+    let phases = &app.shared_data.config.phases;
+    let mut phase_title = match phases.get(phase_index) {
+        Some(phase) => vec![
+            " Phase: ".into(),
+            phase.name.clone().yellow().bold(),
+            format!(" ({}/{}) ", phase_index + 1, phases.len()).into(),
+        ],
+        None => Vec::new(),
+    };
+    if let Some(next) = phases.get(phase_index + 1) {
+        phase_title.push(format!("Next: {} ", next.name).into());
+        phase_title.push(app.key_hint(Action::NextPhase));
+    }
     let instructions = match *app.shared_data.times.lock().unwrap() {
         Some(_) => Title::from(vec![
-            " +1min".into(),
-            " <+> ".blue().bold(),
-            " -1min".into(),
-            " <-> ".blue().bold(),
+            " +1min ".into(),
+            app.key_hint(Action::AddMinute),
+            " -1min ".into(),
+            app.key_hint(Action::SubtractMinute),
+            " Adjust ".into(),
+            app.key_hint(Action::AdjustDuration),
+        ]),
+        None => Title::from(vec![
+            " Start exam ".into(),
+            app.key_hint(Action::StartExam),
+            " Schedule start ".into(),
+            app.key_hint(Action::ScheduleStart),
         ]),
-        None => Title::from(vec![" Start exam".into(), " <Enter> ".blue().bold()]),
     };
     let block = Block::default()
         .title(title.alignment(Alignment::Center))
+        .title(Title::from(phase_title).alignment(Alignment::Right))
         .title(
             instructions
                 .alignment(Alignment::Center)
@@ -154,54 +783,311 @@ fn render_status(app: &App, area: Rect, buf: &mut Buffer) {
         )
         .borders(Borders::ALL)
         .border_set(border::THICK);
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let lines = status_lines(app);
+    let (countdown, style) = big_countdown(app);
+    let [text_area, digits_area] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(big_text_width(&countdown, 1) + 2),
+    ])
+    .areas(inner);
+    let [lines_area, gauge_area] = Layout::vertical([
+        Constraint::Length(lines.len() as u16),
+        Constraint::Length(1),
+    ])
+    .areas(text_area);
+    Paragraph::new(Text::from(lines)).render(lines_area, buf);
+    if let Some(gauge) = progress_gauge(app) {
+        gauge.render(gauge_area, buf);
+    }
+    render_big_text(&countdown, 1, style, digits_area, buf);
+}
+
+/// Height of the timer block, the lines of the groups and the gauge next to the big digits.
+fn status_height(app: &App) -> u16 {
+    let lines = 1 + app.shared_data.groups.len() as u16 + 1;
+    2 + lines.max(BIG_TEXT_HEIGHT)
+}
 
-    let counter_text = match *app.shared_data.times.lock().unwrap() {
+/// Timer of the room and its groups, or what happens next while the timer is not running.
+fn status_lines(app: &App) -> Vec<Line<'static>> {
+    let phase_index = *app.shared_data.phase.lock().unwrap();
+    let phases = &app.shared_data.config.phases;
+    let times = *app.shared_data.times.lock().unwrap();
+    let mut lines = vec![match times {
         Some((start_time, duration)) => {
-            let dur = duration - (Utc::now() - start_time);
-            Line::from(vec![
-                "Start: ".into(),
-                start_time
-                    .with_timezone(&Local)
-                    .format("%H:%M:%S")
-                    .to_string()
-                    .yellow()
-                    .bold(),
-                "  Finish: ".into(),
-                (start_time + duration)
-                    .with_timezone(&Local)
-                    .format("%H:%M:%S")
-                    .to_string()
+            let label = if app.shared_data.groups.is_empty() {
+                None
+            } else {
+                Some("Default")
+            };
+            timer_line(app, label, start_time, duration)
+        }
+        None => match (
+            app.shared_data.phase_end(),
+            *app.shared_data.scheduled_start.lock().unwrap(),
+        ) {
+            (Some(phase_end), _) => {
+                let phase_left = phase_end - Utc::now();
+                let next_phase = phases
+                    .get(phase_index + 1)
+                    .map(|phase| phase.name.clone())
+                    .unwrap_or("Next phase".to_string());
+                Line::from(vec![
+                    "INACTIVE".red().bold(),
+                    format!("  {} starts at ", next_phase).into(),
+                    phase_end
+                        .with_timezone(&Local)
+                        .format("%H:%M:%S")
+                        .to_string()
+                        .yellow()
+                        .bold(),
+                    "  Left: ".into(),
+                    format!(
+                        "{:02}:{:02}min",
+                        phase_left.num_minutes(),
+                        phase_left.num_seconds() % 60
+                    )
                     .yellow()
                     .bold(),
-                "  Duration: ".into(),
-                duration.num_minutes().to_string().yellow().bold(),
-                "min".yellow().bold(),
-                "  Time left: ".into(),
-                format!("{:02}", dur.num_minutes()).yellow().bold(),
-                ":".yellow().bold(),
-                format!("{:02}", dur.num_seconds() % 60).yellow().bold(),
+                ])
+            }
+            (None, Some(start)) => {
+                let until_start = start - Utc::now();
+                Line::from(vec![
+                    "INACTIVE".red().bold(),
+                    "  Scheduled start: ".into(),
+                    start
+                        .with_timezone(&Local)
+                        .format("%H:%M")
+                        .to_string()
+                        .yellow()
+                        .bold(),
+                    format!(
+                        "  (in {}:{:02}min)",
+                        until_start.num_minutes(),
+                        until_start.num_seconds() % 60
+                    )
+                    .into(),
+                ])
+            }
+            (None, None) => Line::from("INACTIVE".red().bold()),
+        },
+    }];
+    for group in &app.shared_data.groups {
+        lines.push(match times {
+            Some((start_time, duration)) => timer_line(
+                app,
+                Some(&group.name),
+                start_time,
+                app.shared_data.duration_of_group(Some(group), duration),
+            ),
+            None => Line::from(vec![
+                format!("{}: ", group.name).bold(),
+                group.duration_minutes.to_string().yellow().bold(),
                 "min".yellow().bold(),
-            ])
+            ]),
+        });
+    }
+    lines
+}
+
+/// Time left of the room, of the phase or until the scheduled start in `MM:SS`,
+/// colored like the milestone that has been reached.
+fn big_countdown(app: &App) -> (String, Style) {
+    let now = Utc::now();
+    let default_style = Style::new().yellow().bold();
+    if let Some((start_time, duration)) = *app.shared_data.times.lock().unwrap() {
+        let time_left = start_time + duration - now;
+        let style = match get_active_milestone(&app.shared_data.config, time_left) {
+            Some(milestone) => {
+                Style::new().fg(Color::from_str(&milestone.color).unwrap_or(Color::Red))
+            }
+            None => default_style,
+        };
+        return (format_countdown(time_left), style);
+    }
+    let upcoming = app
+        .shared_data
+        .phase_end()
+        .or(*app.shared_data.scheduled_start.lock().unwrap());
+    match upcoming {
+        Some(upcoming) => (format_countdown(upcoming - now), default_style),
+        None => ("--:--".to_string(), Style::new().dark_gray()),
+    }
+}
+
+fn format_countdown(time_left: Duration) -> String {
+    let seconds = time_left.num_seconds().max(0);
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Elapsed part of the exam or of the timed phase, `None` if neither is running.
+fn progress_gauge(app: &App) -> Option<Gauge<'static>> {
+    let now = Utc::now();
+    let (start, total) = match *app.shared_data.times.lock().unwrap() {
+        Some(times) => times,
+        None => {
+            let phase_started = (*app.shared_data.phase_started.lock().unwrap())?;
+            (phase_started, app.shared_data.phase_end()? - phase_started)
+        }
+    };
+    let elapsed = (now - start).clamp(Duration::zero(), total);
+    let ratio = if total > Duration::zero() {
+        elapsed.num_seconds() as f64 / total.num_seconds() as f64
+    } else {
+        1.0
+    };
+    let color = match get_active_milestone(&app.shared_data.config, total - elapsed) {
+        Some(milestone) if app.shared_data.times.lock().unwrap().is_some() => {
+            Color::from_str(&milestone.color).unwrap_or(Color::Red)
         }
-        None => Line::from("INACTIVE".red().bold()),
+        _ => Color::Green,
     };
+    Some(
+        Gauge::default()
+            .gauge_style(Style::new().fg(color).on_dark_gray())
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label(format!(
+                "{}min of {}min elapsed ({:.0}%)",
+                elapsed.num_minutes(),
+                total.num_minutes(),
+                ratio * 100.0
+            )),
+    )
+}
 
-    Paragraph::new(counter_text)
-        //.centered()
-        .block(block)
-        .render(area, buf);
+/// Full screen countdown for a projector, readable from the back of the room.
+fn render_projector(app: &App, area: Rect, buf: &mut Buffer) {
+    let phase = app
+        .shared_data
+        .current_phase()
+        .map(|phase| format!(" {} ", phase.name))
+        .unwrap_or_default();
+    let block = Block::default()
+        .title(
+            Title::from(format!(" Room {} ", app.shared_data.room).bold())
+                .alignment(Alignment::Center),
+        )
+        .title(Title::from(phase.yellow().bold()).alignment(Alignment::Right))
+        .title(
+            Title::from(vec![
+                " Leave projector view ".into(),
+                app.key_hint(Action::ToggleProjector),
+            ])
+            .alignment(Alignment::Center)
+            .position(block::Position::Bottom),
+        )
+        .borders(Borders::ALL)
+        .border_set(border::THICK);
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let lines = status_lines(app);
+    let [digits_area, gauge_area, lines_area] = Layout::vertical([
+        Constraint::Min(BIG_TEXT_HEIGHT),
+        Constraint::Length(3),
+        Constraint::Length(lines.len() as u16),
+    ])
+    .areas(inner);
+    let (countdown, style) = big_countdown(app);
+    let digits_area = digits_area.inner(Margin {
+        horizontal: 2,
+        vertical: 1,
+    });
+    let scale = max_scale(&countdown, digits_area);
+    render_big_text(&countdown, scale, style, digits_area, buf);
+    if let Some(gauge) = progress_gauge(app) {
+        gauge.render(
+            gauge_area.inner(Margin {
+                horizontal: 4,
+                vertical: 1,
+            }),
+            buf,
+        );
+    }
+    Paragraph::new(Text::from(lines))
+        .centered()
+        .render(lines_area, buf);
+}
+
+/// Start, finish and time left of the room or of one of its groups.
+fn timer_line(
+    app: &App,
+    label: Option<&str>,
+    start_time: DateTime<Utc>,
+    duration: Duration,
+) -> Line<'static> {
+    let dur = duration - (Utc::now() - start_time);
+    let milestone = get_active_milestone(&app.shared_data.config, dur);
+    let time_left_style = match milestone {
+        Some(milestone) => Style::new()
+            .fg(Color::from_str(&milestone.color).unwrap_or(Color::Red))
+            .bold()
+            .reversed(),
+        None => Style::new().yellow().bold(),
+    };
+    let mut spans = label
+        .map(|label| vec![format!("{}: ", label).bold()])
+        .unwrap_or_default();
+    spans.extend([
+        "Start: ".into(),
+        start_time
+            .with_timezone(&Local)
+            .format("%H:%M:%S")
+            .to_string()
+            .yellow()
+            .bold(),
+        "  Finish: ".into(),
+        (start_time + duration)
+            .with_timezone(&Local)
+            .format("%H:%M:%S")
+            .to_string()
+            .yellow()
+            .bold(),
+        "  Duration: ".into(),
+        duration.num_minutes().to_string().yellow().bold(),
+        "min".yellow().bold(),
+        "  Time left: ".into(),
+        Span::styled(
+            format!("{:02}:{:02}min", dur.num_minutes(), dur.num_seconds() % 60),
+            time_left_style,
+        ),
+    ]);
+    if let Some(milestone) = milestone {
+        spans.push("  ".into());
+        spans.push(Span::styled(milestone.banner.clone(), time_left_style));
+    }
+    Line::from(spans)
 }
 
 fn render_symlinks(app: &App, area: Rect, buf: &mut Buffer) {
     let title = Title::from(" Status ".bold());
     let instructions = Title::from(vec![
-        " Set to dummy".into(),
-        " <d> ".blue().bold(),
-        " Set to real".into(),
-        " <r> ".blue().bold(),
+        " Set to dummy ".into(),
+        app.key_hint(Action::TaskDescriptionDummy),
+        " Set to real ".into(),
+        app.key_hint(Action::TaskDescriptionReal),
     ]);
+    let record = app.shared_data.record.lock().unwrap();
+    // the supervisor can note the head hash to compare it with the exported record later
+    let record_title = Title::from(vec![
+        " Record head ".into(),
+        // a tampered record may hold anything as hash
+        record
+            .head_hash()
+            .get(..RECORD_HASH_PREFIX)
+            .unwrap_or(record.head_hash())
+            .to_string()
+            .yellow(),
+        format!(" ({} events) ", record.entries.len()).into(),
+    ]);
+    drop(record);
     let block = Block::default()
         .title(title.alignment(Alignment::Center))
+        .title(record_title.alignment(Alignment::Right))
         .title(
             instructions
                 .alignment(Alignment::Center)
@@ -211,6 +1097,12 @@ fn render_symlinks(app: &App, area: Rect, buf: &mut Buffer) {
         .border_set(border::THICK);
 
     let counter_text = match app.shared_data.symlink_target.lock().unwrap().as_ref() {
+        Some(path) if app.shared_data.symlink_info.mode == TaskDescriptionMode::Http => {
+            Line::from(vec![
+                "Serving via HTTP under /task/: ".into(),
+                path.clone().yellow().bold(),
+            ])
+        }
         Some(path) => Line::from(vec![
             "Currently pointing to ".into(),
             path.clone().yellow().bold(),
@@ -228,15 +1120,11 @@ fn render_symlinks(app: &App, area: Rect, buf: &mut Buffer) {
         .render(area, buf);
 }
 
-fn render_clients(app: &App, area: Rect, buf: &mut Buffer) {
-    let title = Title::from(" Client Overview ".bold());
+fn render_nonclient_alert(app: &App, area: Rect, buf: &mut Buffer) {
+    let title = Title::from(" UNAUTHORIZED TIMER ACCESS ".bold().white().on_red());
     let instructions = Title::from(vec![
-        " Quit ".into(),
-        "<q> ".blue().bold(),
-        "  Enable keys ".into(),
-        "<Escape> ".blue().bold(),
-        "  Disable keys ".into(),
-        "<Space> ".blue().bold(),
+        " Acknowledge ".into(),
+        app.key_hint(Action::AcknowledgeNonclients),
     ]);
     let block = Block::default()
         .title(title.alignment(Alignment::Center))
@@ -246,85 +1134,565 @@ fn render_clients(app: &App, area: Rect, buf: &mut Buffer) {
                 .position(block::Position::Bottom),
         )
         .borders(Borders::ALL)
-        .border_set(border::THICK);
+        .border_set(border::THICK)
+        .border_style(Style::new().red());
 
+    let nonclients = app.shared_data.nonclients.lock().unwrap();
+    let unacknowledged = nonclients
+        .iter()
+        .filter(|nonclient| nonclient.is_unacknowledged())
+        .collect::<Vec<_>>();
     let mut lines = Vec::new();
-    lines.push(Line::from(vec![
-        "kbd layer  name      IP address       time since timer request".bold(),
-    ]));
-    lines.append(
-        &mut app
-            .shared_data
-            .clients
-            .iter()
-            .map(|client| {
-                Line::from(vec![
-                    try_pad_string(
-                        match client.current_layer.lock().unwrap().as_ref() {
-                            Some(layer) => layer.clone(),
-                            None => "  ---".to_string(),
-                        },
-                        ' ',
-                        11,
-                    )
+    for nonclient in unacknowledged.iter().take(MAX_ALERT_ENTRIES) {
+        lines.push(Line::from(vec![
+            nonclient.ip_address.to_string().red().bold(),
+            "  hits: ".into(),
+            nonclient.hit_count.to_string().yellow().bold(),
+            "  first: ".into(),
+            nonclient
+                .first_timer_access
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string()
+                .yellow(),
+            "  last: ".into(),
+            nonclient
+                .last_timer_access
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string()
+                .yellow(),
+            "  path: ".into(),
+            nonclient.last_path.clone().yellow(),
+        ]));
+        lines.push(Line::from(vec![
+            "    user agent: ".into(),
+            nonclient
+                .user_agent
+                .clone()
+                .unwrap_or("unknown".to_string())
+                .italic(),
+        ]));
+    }
+    if unacknowledged.len() > MAX_ALERT_ENTRIES {
+        lines.pop();
+        lines.push(Line::from(format!(
+            "    ... and {} more addresses",
+            unacknowledged.len() - MAX_ALERT_ENTRIES
+        )));
+    }
+
+    Paragraph::new(Text::from(lines))
+        .block(block)
+        .render(area, buf);
+}
+
+fn render_event_log(app: &App, area: Rect, buf: &mut Buffer) {
+    let event_log = &app.shared_data.event_log;
+    let mut title = vec![" Event Log ".bold()];
+    let errors = event_log.count_at_least(Severity::Error);
+    let warnings = event_log.count_at_least(Severity::Warning) - errors;
+    if errors > 0 {
+        title.push(
+            format!(" {} error{} ", errors, if errors == 1 { "" } else { "s" })
+                .white()
+                .bold()
+                .on_red(),
+        );
+    }
+    if warnings > 0 {
+        title.push(
+            format!(
+                " {} warning{} ",
+                warnings,
+                if warnings == 1 { "" } else { "s" }
+            )
+            .black()
+            .on_yellow(),
+        );
+    }
+    let (instruction, count) = if app.event_log_expanded {
+        (" Collapse ", EVENT_LOG_LINES)
+    } else {
+        (" Expand ", 1)
+    };
+    let block = Block::default()
+        .title(Title::from(title).alignment(Alignment::Center))
+        .title(
+            Title::from(vec![
+                instruction.into(),
+                app.key_hint(Action::ToggleEventLog),
+            ])
+            .alignment(Alignment::Center)
+            .position(block::Position::Bottom),
+        )
+        .borders(Borders::ALL)
+        .border_set(border::THICK);
+
+    let mut lines = event_log
+        .newest(count)
+        .into_iter()
+        .map(|entry| {
+            let severity = match entry.severity {
+                Severity::Info => entry.severity.label().green(),
+                Severity::Warning => entry.severity.label().yellow().bold(),
+                Severity::Error => entry.severity.label().red().bold(),
+            };
+            Line::from(vec![
+                entry
+                    .time
+                    .with_timezone(&Local)
+                    .format("%H:%M:%S ")
+                    .to_string()
                     .yellow(),
-                    try_pad_string(client.name.clone(), ' ', 10).into(),
-                    try_pad_string(client.ip_address.to_string(), ' ', 18).into(),
-                    match client.last_timer_access.lock().unwrap().as_ref() {
-                        Some(last_access) => {
-                            let duration = Utc::now() - last_access;
-                            format!(
-                                "{}:{:02}min",
-                                duration.num_minutes(),
-                                duration.num_seconds() % 60
-                            )
-                        }
-                        None => " -".to_string(),
-                    }
-                    .into(),
-                ])
-            })
-            .collect::<Vec<_>>(),
+                severity,
+                format!(" {}: ", entry.source).dark_gray(),
+                entry.message.into(),
+                match entry.repeats {
+                    0 => "".into(),
+                    repeats => format!(" ({} more times)", repeats).dark_gray(),
+                },
+            ])
+        })
+        .collect::<Vec<_>>();
+    if lines.is_empty() {
+        lines.push(Line::from("no events yet".dark_gray()));
+    }
+
+    Paragraph::new(Text::from(lines))
+        .block(block)
+        .render(area, buf);
+}
+
+fn render_notes(app: &App, area: Rect, buf: &mut Buffer) {
+    let Some(index) = app.selected_visible_client() else {
+        return;
+    };
+    let client = &app.shared_data.clients[index];
+    let title = match client.student.lock().unwrap().as_ref() {
+        Some(student) => format!(" Notes of {} ({}) ", client.name, student.display()),
+        None => format!(" Notes of {} ", client.name),
+    };
+    let title = Title::from(title.bold());
+    let block = Block::default()
+        .title(title.alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_set(border::THICK);
+
+    let notes = client.notes.lock().unwrap();
+    let lines = notes
+        .iter()
+        .skip(notes.len().saturating_sub(MAX_NOTE_LINES))
+        .map(|note| {
+            Line::from(vec![
+                note.time
+                    .with_timezone(&Local)
+                    .format("%H:%M:%S ")
+                    .to_string()
+                    .yellow(),
+                note.text.clone().into(),
+            ])
+        })
+        .collect::<Vec<_>>();
+
+    Paragraph::new(Text::from(lines))
+        .block(block)
+        .render(area, buf);
+}
+
+fn render_clients(app: &App, table_state: &mut TableState, area: Rect, buf: &mut Buffer) {
+    let visible = visible_clients(&app.shared_data, app.sort_column, app.client_filter);
+    let mut title = format!(
+        " Client Overview ({}/{}",
+        visible.len(),
+        app.shared_data.clients.len()
     );
-    if app.shared_data.config.tui_show_nonclient_timer_accesses {
-        lines.append(
-            &mut app
-                .shared_data
-                .nonclients
-                .lock()
-                .unwrap()
+    if app.client_filter != ClientFilter::All {
+        title.push_str(&format!(", {}", app.client_filter.label()));
+    }
+    title.push_str(") ");
+    let title = Title::from(title.bold());
+    let mut instructions = vec![
+        " Help ".into(),
+        app.key_hint(Action::Help),
+        "  Quit ".into(),
+        app.key_hint(Action::Quit),
+        "  Enable keys ".into(),
+        app.key_hint(Action::EnableKeyboards),
+        "  Disable keys ".into(),
+        app.key_hint(Action::DisableKeyboards),
+    ];
+    if app.shared_data.collection_info.is_some() {
+        instructions.push("  Collect work ".into());
+        instructions.push(app.key_hint(Action::CollectWork));
+    }
+    instructions.push("  Export record ".into());
+    instructions.push(app.key_hint(Action::ExportRecord));
+    instructions.push("  Add note ".into());
+    instructions.push(app.key_hint(Action::AddNote));
+    if !app.shared_data.groups.is_empty() {
+        instructions.push("  Group ".into());
+        instructions.push(app.key_hint(Action::SetGroup));
+    }
+    instructions.push("  Finished ".into());
+    instructions.push(app.key_hint(Action::ToggleFinished));
+    instructions.push("  Assign student ".into());
+    instructions.push(app.key_hint(Action::AssignStudent));
+    instructions.push("  Import roster ".into());
+    instructions.push(app.key_hint(Action::ImportRoster));
+    instructions.push("  Sort ".into());
+    instructions.push(app.key_hint(Action::SortNext));
+    instructions.push("  Filter ".into());
+    instructions.push(app.key_hint(Action::CycleFilter));
+    if app.room_layout.is_some() {
+        instructions.push("  Seat map ".into());
+        instructions.push(app.key_hint(Action::ToggleSeatmap));
+    }
+    let instructions = Title::from(instructions);
+    let mut block = Block::default()
+        .title(title.alignment(Alignment::Center))
+        .title(
+            instructions
+                .alignment(Alignment::Center)
+                .position(block::Position::Bottom),
+        )
+        .borders(Borders::ALL)
+        .border_set(border::THICK);
+
+    if let Some(message) = &app.message {
+        block =
+            block.title(Title::from(format!(" {} ", message).italic()).alignment(Alignment::Right));
+    }
+    if let Some(last) = app.history.last() {
+        block = block.title(
+            Title::from(vec![
+                format!(" Undo '{}' ", last.description).into(),
+                app.key_hint(Action::Undo),
+                format!("({} in history) ", app.history.len()).dark_gray(),
+            ])
+            .alignment(Alignment::Left),
+        );
+    }
+
+    let has_groups = !app.shared_data.groups.is_empty();
+    let mut header = vec!["kbd layer", "name"];
+    if has_groups {
+        header.push("group");
+    }
+    header.extend([
+        "student",
+        "IP address",
+        "finish",
+        "time since timer request",
+        "heartbeat",
+        "submission",
+        "collection",
+        "notes",
+    ]);
+    let header = header
+        .into_iter()
+        .map(|name| {
+            if Some(name) == app.sort_column.header() {
+                format!("{} ▲", name)
+            } else {
+                name.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut rows = visible
+        .iter()
+        .map(|&index| {
+            let client = &app.shared_data.clients[index];
+            let mut cells = vec![layer_span(client), client.name.clone().into()];
+            if has_groups {
+                cells.push(group_span(client));
+            }
+            cells.extend([
+                student_span(client.student.lock().unwrap().as_ref()),
+                client.ip_address.to_string().into(),
+                finish_span(app, client),
+                last_access_span(*client.last_timer_access.lock().unwrap()),
+                heartbeat_span(app, *client.last_heartbeat.lock().unwrap()),
+                submission_span(&client.submissions.lock().unwrap()),
+                collection_span(client.collection_status.lock().unwrap().as_ref()),
+                notes_span(&client.notes.lock().unwrap()),
+            ]);
+            cells
+        })
+        .collect::<Vec<_>>();
+    // nonclients are listed below the clients with only address and last access
+    if app.shared_data.config.tui_show_nonclient_timer_accesses
+        && app.client_filter == ClientFilter::All
+    {
+        let ip_column = if has_groups { 4 } else { 3 };
+        for nonclient in app.shared_data.nonclients.lock().unwrap().iter() {
+            let mut cells = vec![Span::from(""); header.len()];
+            cells[ip_column] = nonclient.ip_address.to_string().red();
+            cells[ip_column + 2] = format!(
+                "{}  ({} hits)",
+                format_elapsed(Utc::now() - nonclient.last_timer_access),
+                nonclient.hit_count
+            )
+            .red();
+            rows.push(cells);
+        }
+    }
+
+    // every column is as wide as its widest cell
+    let widths = header
+        .iter()
+        .enumerate()
+        .map(|(column, name)| {
+            let width = rows
                 .iter()
-                .map(|nonclient| {
-                    Line::from(vec![
-                        "                     ".into(),
-                        try_pad_string(nonclient.ip_address.to_string(), ' ', 18).into(),
-                        {
-                            let duration = Utc::now() - nonclient.last_timer_access;
-                            format!(
-                                "{}:{:02}min",
-                                duration.num_minutes(),
-                                duration.num_seconds() % 60
-                            )
-                        }
-                        .into(),
-                    ])
-                })
-                .collect::<Vec<_>>(),
+                .map(|cells| cells[column].width())
+                .chain([name.chars().count()])
+                .max()
+                .unwrap_or(0);
+            Constraint::Length(width as u16)
+        })
+        .collect::<Vec<_>>();
+    let row_count = rows.len();
+    let table = Table::new(
+        rows.into_iter()
+            .map(|cells| Row::new(cells.into_iter().map(Cell::from))),
+        widths,
+    )
+    .header(Row::new(header).bold())
+    .column_spacing(2)
+    .highlight_style(Style::new().on_dark_gray())
+    .block(block);
+    table_state.select(
+        visible
+            .iter()
+            .position(|&index| index == app.selected_client),
+    );
+    StatefulWidget::render(table, area, buf, table_state);
+
+    // borders and header take three lines
+    let viewport = area.height.saturating_sub(3) as usize;
+    if row_count > viewport {
+        let mut scrollbar_state = ScrollbarState::new(row_count - viewport)
+            .viewport_content_length(viewport)
+            .position(table_state.offset());
+        let rows_area = Rect {
+            y: area.y + 2,
+            height: area.height - 3,
+            ..area
+        };
+        Scrollbar::new(ScrollbarOrientation::VerticalRight).render(
+            rows_area,
+            buf,
+            &mut scrollbar_state,
         );
     }
-    let counter_text = Text::from(lines);
+}
 
-    Paragraph::new(counter_text)
-        //.centered()
-        .block(block)
-        .render(area, buf);
+fn format_elapsed(duration: Duration) -> String {
+    format!(
+        "{}:{:02}min",
+        duration.num_minutes(),
+        duration.num_seconds() % 60
+    )
+}
+
+fn last_access_span(last_access: Option<DateTime<Utc>>) -> Span<'static> {
+    match last_access {
+        Some(last_access) => format_elapsed(Utc::now() - last_access).into(),
+        None => " -".into(),
+    }
+}
+
+fn heartbeat_span(app: &App, last_heartbeat: Option<DateTime<Utc>>) -> Span<'static> {
+    match last_heartbeat {
+        Some(last_heartbeat) => {
+            let duration = Utc::now() - last_heartbeat;
+            if duration.num_seconds() > app.shared_data.config.tui_heartbeat_timeout_seconds {
+                format!("MISSING {}", format_elapsed(duration)).red().bold()
+            } else {
+                "ok".green()
+            }
+        }
+        // a kiosk that never opened the countdown page is the case to notice
+        None if app.shared_data.times.lock().unwrap().is_some() => "MISSING".red().bold(),
+        None => "never".dark_gray(),
+    }
+}
+
+fn submission_span(submissions: &[Submission]) -> Span<'static> {
+    match submissions.last() {
+        Some(last) => format!(
+            "{} file{}, last {}",
+            submissions.len(),
+            if submissions.len() == 1 { "" } else { "s" },
+            last.time.with_timezone(&Local).format("%H:%M:%S")
+        )
+        .green(),
+        None => " -".into(),
+    }
+}
+
+fn collection_span(status: Option<&CollectionStatus>) -> Span<'static> {
+    match status {
+        Some(CollectionStatus::Running) => "collecting...".yellow(),
+        Some(CollectionStatus::Collected { time, file_count }) => format!(
+            "{} files at {}",
+            file_count,
+            time.with_timezone(&Local).format("%H:%M:%S")
+        )
+        .green(),
+        Some(CollectionStatus::MissingDirectory) => "directory missing".red().bold(),
+        Some(CollectionStatus::Failed(err)) => format!("failed: {}", err).red().bold(),
+        None => " -".into(),
+    }
+}
+
+/// Layers that drifted from the intended one stand out until they are corrected.
+fn layer_span(client: &Client) -> Span<'static> {
+    let text = match client.current_layer.lock().unwrap().as_ref() {
+        Some(layer) => layer.clone(),
+        None => "  ---".to_string(),
+    };
+    if client.layer_drifted() {
+        format!("{}!", text).white().bold().on_red()
+    } else {
+        text.yellow()
+    }
+}
+
+fn group_span(client: &Client) -> Span<'static> {
+    match client.group.lock().unwrap().as_ref() {
+        Some(group) => group.clone().magenta(),
+        None => " -".dark_gray(),
+    }
+}
+
+fn finish_span(app: &App, client: &Client) -> Span<'static> {
+    if client.finished.lock().unwrap().is_some() {
+        return "SUBMITTED".green().bold();
+    }
+    let span: Span = match app.shared_data.finish_time_of_client(client) {
+        Some(finish_time) => finish_time
+            .with_timezone(&Local)
+            .format("%H:%M:%S")
+            .to_string()
+            .into(),
+        None => " -".into(),
+    };
+    // individual finish times stand out from the rest of the room
+    if client.start_override.lock().unwrap().is_some() {
+        span.cyan().bold()
+    } else {
+        span
+    }
+}
+
+fn student_span(student: Option<&Student>) -> Span<'static> {
+    match student {
+        Some(student) => student.display().cyan(),
+        None => " -".dark_gray(),
+    }
+}
+
+/// `now`, a time of today like `09:15` or empty for the start of the room.
+fn parse_start_override(text: &str) -> Result<Option<DateTime<Utc>>, String> {
+    match text {
+        "" => Ok(None),
+        "now" => Ok(Some(Utc::now())),
+        text => parse_time_of_day(text).map(Some),
+    }
+}
+
+/// Previews the individual finish time the text typed into the start prompt results in.
+fn start_override_hint(app: &App, client_index: usize, text: &str) -> Line<'static> {
+    let Some((start_time, duration)) = *app.shared_data.times.lock().unwrap() else {
+        return Line::from("the timer is not running".red());
+    };
+    // the client finishes after the duration of its group, not the one of the room
+    let duration = match app.shared_data.clients.get(client_index) {
+        Some(client) => app
+            .shared_data
+            .duration_of_group(app.shared_data.group_of_client(client), duration),
+        None => duration,
+    };
+    match parse_start_override(text) {
+        Ok(start) => Line::from(vec![
+            "Finish: ".into(),
+            (start.unwrap_or(start_time) + duration)
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string()
+                .yellow()
+                .bold(),
+            if start.is_none() {
+                "  (start of the room)".into()
+            } else {
+                "".into()
+            },
+        ]),
+        Err(err) => Line::from(err.red()),
+    }
+}
+
+/// Previews the finish time the text typed into the adjustment prompt results in.
+fn adjustment_hint(app: &App, text: &str) -> Line<'static> {
+    let Some((start_time, duration)) = *app.shared_data.times.lock().unwrap() else {
+        return Line::from("the timer is not running".red());
+    };
+    match parse_adjustment(text)
+        .and_then(|adjustment| apply_adjustment(start_time, duration, adjustment))
+        .and_then(|new_duration| {
+            app.shared_data
+                .check_group_durations(start_time, new_duration)
+                .map(|()| new_duration)
+        }) {
+        Ok(new_duration) => Line::from(vec![
+            "New finish: ".into(),
+            (start_time + new_duration)
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string()
+                .yellow()
+                .bold(),
+            "  Duration: ".into(),
+            format!("{}min", new_duration.num_minutes()).yellow().bold(),
+            format!(" ({:+}min)", (new_duration - duration).num_minutes()).into(),
+        ]),
+        Err(err) => Line::from(err.red()),
+    }
+}
+
+/// Shows which student the text typed into the assignment prompt resolves to.
+fn student_hint(app: &App, client_index: usize, text: &str) -> Line<'static> {
+    let roster = app.shared_data.roster.lock().unwrap();
+    if text.is_empty() {
+        return Line::from("seat will be freed".dark_gray());
+    }
+    if roster.is_empty() {
+        return Line::from("no roster loaded, input is used as matriculation number".dark_gray());
+    }
+    match search_student(&roster, text) {
+        Some(student) => {
+            let seated_at = app
+                .shared_data
+                .clients
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != client_index)
+                .map(|(_, client)| client)
+                .find(|client| client.student.lock().unwrap().as_ref() == Some(&student))
+                .map(|client| format!(" (moves from {})", client.name))
+                .unwrap_or_default();
+            Line::from(vec![student.display().cyan().bold(), seated_at.yellow()])
+        }
+        None => Line::from("no unique match in roster".red()),
+    }
 }
 
-fn try_pad_string(mut string: String, pad_char: char, desired_length: usize) -> String {
-    let pad_len = desired_length as isize - string.len() as isize;
-    if pad_len > 0 {
-        let padding = repeat(pad_char).take(pad_len as usize).collect::<String>();
-        string.push_str(&padding);
+fn notes_span(notes: &[Note]) -> Span<'static> {
+    match notes.len() {
+        0 => " -".into(),
+        count => format!("✎ {}", count).magenta().bold(),
     }
-    string
 }
@@ -0,0 +1,512 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    prelude::*,
+    symbols::border,
+    widgets::{block::*, *},
+};
+use serde::{Deserialize, Serialize};
+
+/// a hold is released once the terminal stops repeating the key for this long,
+/// has to exceed the initial delay of the key repeat
+const HOLD_RELEASE_GAP: Duration = Duration::from_millis(800);
+
+/// Everything the supervisor can do with a single key in the TUI.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    StartExam,
+    ScheduleStart,
+    AddMinute,
+    SubtractMinute,
+    AdjustDuration,
+    NextPhase,
+    Undo,
+    DisableKeyboards,
+    EnableKeyboards,
+    TaskDescriptionDummy,
+    TaskDescriptionReal,
+    CollectWork,
+    ExportRecord,
+    AcknowledgeNonclients,
+    AddNote,
+    AssignStudent,
+    ImportRoster,
+    StartOverride,
+    ToggleFinished,
+    SetGroup,
+    SortNext,
+    SortPrevious,
+    CycleFilter,
+    ToggleSeatmap,
+    ToggleProjector,
+    ToggleEventLog,
+    Help,
+    Quit,
+}
+
+impl Action {
+    /// all actions in the order of the help overlay
+    pub const ALL: [Action; 28] = [
+        Action::StartExam,
+        Action::ScheduleStart,
+        Action::AddMinute,
+        Action::SubtractMinute,
+        Action::AdjustDuration,
+        Action::NextPhase,
+        Action::Undo,
+        Action::DisableKeyboards,
+        Action::EnableKeyboards,
+        Action::TaskDescriptionDummy,
+        Action::TaskDescriptionReal,
+        Action::CollectWork,
+        Action::ExportRecord,
+        Action::AcknowledgeNonclients,
+        Action::AddNote,
+        Action::AssignStudent,
+        Action::ImportRoster,
+        Action::StartOverride,
+        Action::ToggleFinished,
+        Action::SetGroup,
+        Action::SortNext,
+        Action::SortPrevious,
+        Action::CycleFilter,
+        Action::ToggleSeatmap,
+        Action::ToggleProjector,
+        Action::ToggleEventLog,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::StartExam => "start the exam",
+            Action::ScheduleStart => "schedule the start",
+            Action::AddMinute => "add one minute",
+            Action::SubtractMinute => "subtract one minute",
+            Action::AdjustDuration => "adjust the duration",
+            Action::NextPhase => "advance to the next phase",
+            Action::Undo => "undo the last action",
+            Action::DisableKeyboards => "disable all keyboards",
+            Action::EnableKeyboards => "enable all keyboards",
+            Action::TaskDescriptionDummy => "set the task description to dummy",
+            Action::TaskDescriptionReal => "set the task description to real",
+            Action::CollectWork => "collect the work of all clients",
+            Action::ExportRecord => "export the exam record",
+            Action::AcknowledgeNonclients => "acknowledge unauthorized timer accesses",
+            Action::AddNote => "add a note to the selected client",
+            Action::AssignStudent => "assign a student to the selected client",
+            Action::ImportRoster => "import a roster",
+            Action::StartOverride => "set an individual start of the selected client",
+            Action::ToggleFinished => "mark the selected client as finished or reopen it",
+            Action::SetGroup => "set the group of the selected client",
+            Action::SortNext => "sort the clients by the next column",
+            Action::SortPrevious => "sort the clients by the previous column",
+            Action::CycleFilter => "filter the clients",
+            Action::ToggleSeatmap => "switch between client list and seat map",
+            Action::ToggleProjector => "switch to the projector view and back",
+            Action::ToggleEventLog => "expand or collapse the event log",
+            Action::Help => "show the key bindings",
+            Action::Quit => "quit and delete the session",
+        }
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Action::StartExam => KeyCode::Enter,
+            Action::ScheduleStart => KeyCode::Char('t'),
+            Action::AddMinute => KeyCode::Char('+'),
+            Action::SubtractMinute => KeyCode::Char('-'),
+            Action::AdjustDuration => KeyCode::Char('e'),
+            Action::NextPhase => KeyCode::Char('p'),
+            Action::Undo => KeyCode::Char('u'),
+            Action::DisableKeyboards => KeyCode::Char(' '),
+            Action::EnableKeyboards => KeyCode::Esc,
+            Action::TaskDescriptionDummy => KeyCode::Char('d'),
+            Action::TaskDescriptionReal => KeyCode::Char('r'),
+            Action::CollectWork => KeyCode::Char('c'),
+            Action::ExportRecord => KeyCode::Char('x'),
+            Action::AcknowledgeNonclients => KeyCode::Char('a'),
+            Action::AddNote => KeyCode::Char('n'),
+            Action::AssignStudent => KeyCode::Char('s'),
+            Action::ImportRoster => KeyCode::Char('i'),
+            Action::StartOverride => KeyCode::Char('o'),
+            Action::ToggleFinished => KeyCode::Char('f'),
+            Action::SetGroup => KeyCode::Char('g'),
+            Action::SortNext => KeyCode::Tab,
+            Action::SortPrevious => KeyCode::BackTab,
+            Action::CycleFilter => KeyCode::Char('/'),
+            Action::ToggleSeatmap => KeyCode::Char('m'),
+            Action::ToggleProjector => KeyCode::Char('v'),
+            Action::ToggleEventLog => KeyCode::Char('L'),
+            Action::Help => KeyCode::Char('?'),
+            Action::Quit => KeyCode::Char('q'),
+        }
+    }
+
+    /// name of the action in `keybindings` and `tui_confirmations` of the config
+    fn config_name(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+}
+
+/// Keys of all actions, the defaults with the remappings of the config applied.
+#[derive(Debug)]
+pub struct KeyBindings {
+    actions: HashMap<KeyCode, Action>,
+    keys: HashMap<Action, KeyCode>,
+}
+
+impl KeyBindings {
+    /// Fails on keys that can not be parsed and on keys bound to more than one action.
+    pub fn new(remappings: &HashMap<Action, String>) -> Result<Self, String> {
+        let mut keys = HashMap::new();
+        for action in Action::ALL {
+            let key = match remappings.get(&action) {
+                Some(key) => parse_key(key).ok_or(format!(
+                    "unknown key '{}' for {}",
+                    key,
+                    action.config_name()
+                ))?,
+                None => action.default_key(),
+            };
+            keys.insert(action, key);
+        }
+        let mut actions = HashMap::new();
+        for action in Action::ALL {
+            let key = keys[&action];
+            if let Some(other) = actions.insert(key, action) {
+                return Err(format!(
+                    "key '{}' is bound to both {} and {}",
+                    key_name(key),
+                    other.config_name(),
+                    action.config_name()
+                ));
+            }
+        }
+        Ok(KeyBindings { actions, keys })
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.actions.get(&key).copied()
+    }
+
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys[&action]
+    }
+
+    /// Draws all bindings with their description as popup centered in `area`.
+    pub fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        let width = area.width.saturating_sub(4).min(90);
+        let height = Action::ALL.len() as u16 + 3;
+        let popup = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height.min(area.height),
+        );
+        let block = Block::default()
+            .title(Title::from(" Key Bindings ".bold()).alignment(Alignment::Center))
+            .title(
+                Title::from(" any key closes the help ".italic())
+                    .alignment(Alignment::Center)
+                    .position(block::Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(border::THICK);
+        let rows = Action::ALL.iter().map(|action| {
+            Row::new([
+                Cell::from(format!("<{}>", key_name(self.key(*action))).blue().bold()),
+                Cell::from(capitalize(action.description())),
+                Cell::from(action.config_name().dark_gray()),
+            ])
+        });
+        let navigation = Row::new([
+            Cell::from("<Up/Down>".blue().bold()),
+            Cell::from("Select client, also PageUp, PageDown, Home and End"),
+            Cell::from(""),
+        ]);
+        Clear.render(popup, buf);
+        Widget::render(
+            Table::new(
+                rows.chain([navigation]),
+                [
+                    Constraint::Length(12),
+                    Constraint::Min(20),
+                    Constraint::Length(24),
+                ],
+            )
+            .block(block),
+            popup,
+            buf,
+        );
+    }
+}
+
+/// Key names of the config: a single character or one of `space`, `enter`, `esc`, `tab`,
+/// `backtab`, `backspace`, `delete`, `insert` and `f1` to `f12`.
+fn parse_key(text: &str) -> Option<KeyCode> {
+    let mut chars = text.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let key = match text.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        name => KeyCode::F(
+            name.strip_prefix('f')?
+                .parse()
+                .ok()
+                .filter(|n| (1..=12).contains(n))?,
+        ),
+    };
+    Some(key)
+}
+
+/// How an action has to be confirmed before it is executed.
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationMode {
+    #[default]
+    None,
+    /// the key has to be pressed a second time within `tui_confirmation_ms`
+    DoublePress,
+    /// the name of the room has to be typed into a prompt
+    TypeRoomName,
+    /// the key has to be held down for `tui_confirmation_ms`
+    Hold,
+}
+
+/// A hold has to outlast the gap that tells a released key from key repeats, otherwise a
+/// single tap would already confirm it.
+pub fn check_hold_duration(
+    confirmations: &HashMap<Action, ConfirmationMode>,
+    confirmation_ms: u64,
+) -> Result<(), String> {
+    let uses_hold = confirmations
+        .values()
+        .any(|mode| *mode == ConfirmationMode::Hold);
+    let minimum = HOLD_RELEASE_GAP.as_millis() as u64;
+    if uses_hold && confirmation_ms <= minimum {
+        return Err(format!(
+            "tui_confirmation_ms has to be more than {} for hold confirmations",
+            minimum
+        ));
+    }
+    Ok(())
+}
+
+pub enum PendingState {
+    Waiting,
+    Confirmed,
+    Aborted,
+    /// the pending action is over, the key is handled as usual
+    Finished,
+}
+
+/// An action that waits for its confirmation by double press or hold.
+#[derive(Debug)]
+pub struct PendingAction {
+    pub action: Action,
+    pub mode: ConfirmationMode,
+    key: KeyCode,
+    duration: Duration,
+    since: Instant,
+    last_press: Instant,
+    /// a confirmed hold swallows the key repeats until the key is released
+    held_to_end: bool,
+}
+
+impl PendingAction {
+    pub fn new(action: Action, key: KeyCode, mode: ConfirmationMode, duration: Duration) -> Self {
+        let now = Instant::now();
+        PendingAction {
+            action,
+            mode,
+            key,
+            duration,
+            since: now,
+            last_press: now,
+            held_to_end: false,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> PendingState {
+        if self.held_to_end {
+            if key != self.key {
+                return PendingState::Finished;
+            }
+            self.last_press = Instant::now();
+            return PendingState::Waiting;
+        }
+        match self.mode {
+            ConfirmationMode::DoublePress if key == self.key => PendingState::Confirmed,
+            ConfirmationMode::Hold if key == self.key => {
+                self.last_press = Instant::now();
+                PendingState::Waiting
+            }
+            _ => PendingState::Aborted,
+        }
+    }
+
+    /// Checks whether the pending action timed out or was held long enough.
+    pub fn tick(&mut self) -> PendingState {
+        let released = self.last_press.elapsed() > HOLD_RELEASE_GAP;
+        if self.held_to_end {
+            return if released {
+                PendingState::Finished
+            } else {
+                PendingState::Waiting
+            };
+        }
+        match self.mode {
+            ConfirmationMode::Hold if self.since.elapsed() >= self.duration => {
+                self.held_to_end = true;
+                PendingState::Confirmed
+            }
+            ConfirmationMode::Hold if released => PendingState::Aborted,
+            _ if self.since.elapsed() > self.duration => PendingState::Aborted,
+            _ => PendingState::Waiting,
+        }
+    }
+
+    /// Draws the countdown of the pending action as popup centered in `area`.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        if self.held_to_end {
+            return;
+        }
+        let width = area.width.saturating_sub(4).min(70);
+        let height = 4;
+        let popup = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height.min(area.height),
+        );
+        let elapsed = self.since.elapsed().min(self.duration);
+        let left = self.duration - elapsed;
+        let (instruction, ratio) = match self.mode {
+            ConfirmationMode::Hold => (
+                format!("Keep holding <{}>", key_name(self.key)),
+                elapsed.as_secs_f64() / self.duration.as_secs_f64(),
+            ),
+            _ => (
+                format!("Press <{}> again", key_name(self.key)),
+                left.as_secs_f64() / self.duration.as_secs_f64(),
+            ),
+        };
+        let block = Block::default()
+            .title(
+                Title::from(format!(" {}? ", capitalize(self.action.description())).bold())
+                    .alignment(Alignment::Center),
+            )
+            .title(
+                Title::from(" any other key aborts ".italic())
+                    .alignment(Alignment::Center)
+                    .position(block::Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::new().yellow());
+        Clear.render(popup, buf);
+        Gauge::default()
+            .block(block)
+            .gauge_style(Style::new().yellow().on_dark_gray())
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label(format!("{}  {:.1}s", instruction, left.as_secs_f64()))
+            .render(popup, buf);
+    }
+}
+
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Esc => "Escape".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        key => format!("{:?}", key),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_single_characters_and_key_names() {
+        assert_eq!(parse_key("q"), Some(KeyCode::Char('q')));
+        assert_eq!(parse_key("Q"), Some(KeyCode::Char('Q')));
+        assert_eq!(parse_key("+"), Some(KeyCode::Char('+')));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("Enter"), Some(KeyCode::Enter));
+        assert_eq!(parse_key("esc"), Some(KeyCode::Esc));
+        assert_eq!(parse_key("ESCAPE"), Some(KeyCode::Esc));
+        assert_eq!(parse_key("backtab"), Some(KeyCode::BackTab));
+        assert_eq!(parse_key("f1"), Some(KeyCode::F(1)));
+        assert_eq!(parse_key("F12"), Some(KeyCode::F(12)));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for text in ["", "qq", "f0", "f13", "fx", "ctrl+c", "up"] {
+            assert_eq!(parse_key(text), None, "{} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn applies_remappings_to_the_defaults() {
+        let defaults = KeyBindings::new(&HashMap::new()).unwrap();
+        assert_eq!(defaults.key(Action::Quit), KeyCode::Char('q'));
+        assert_eq!(defaults.action(KeyCode::Char('q')), Some(Action::Quit));
+
+        let remappings = HashMap::from([(Action::Quit, "Q".to_string())]);
+        let key_bindings = KeyBindings::new(&remappings).unwrap();
+        assert_eq!(key_bindings.key(Action::Quit), KeyCode::Char('Q'));
+        assert_eq!(key_bindings.action(KeyCode::Char('q')), None);
+        assert_eq!(key_bindings.key(Action::Undo), KeyCode::Char('u'));
+    }
+
+    #[test]
+    fn rejects_unknown_and_conflicting_bindings() {
+        let unknown = HashMap::from([(Action::Quit, "ctrl+q".to_string())]);
+        assert_eq!(
+            KeyBindings::new(&unknown).unwrap_err(),
+            "unknown key 'ctrl+q' for quit"
+        );
+        let conflict = HashMap::from([(Action::Quit, "u".to_string())]);
+        let err = KeyBindings::new(&conflict).unwrap_err();
+        assert!(err.starts_with("key 'u' is bound to both"), "{}", err);
+    }
+
+    #[test]
+    fn hold_has_to_outlast_the_release_gap() {
+        let hold = HashMap::from([(Action::Quit, ConfirmationMode::Hold)]);
+        assert!(check_hold_duration(&hold, 500).is_err());
+        assert!(check_hold_duration(&hold, 800).is_err());
+        assert!(check_hold_duration(&hold, 2000).is_ok());
+        // a short double press is fine
+        let double_press = HashMap::from([(Action::Quit, ConfirmationMode::DoublePress)]);
+        assert!(check_hold_duration(&double_press, 500).is_ok());
+    }
+}
@@ -0,0 +1,78 @@
+use ratatui::prelude::*;
+
+/// lines of a character at scale 1
+pub const BIG_TEXT_HEIGHT: u16 = 5;
+/// terminal cells are about twice as high as wide, so every pixel takes two columns
+const PIXEL_WIDTH: u16 = 2;
+
+/// Pixels of the characters the countdown consists of, unknown characters are blank.
+fn glyph(c: char) -> [&'static str; BIG_TEXT_HEIGHT as usize] {
+    match c {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => ["  #", "  #", "  #", "  #", "  #"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", "###", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        ':' => [" ", "#", " ", "#", " "],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        _ => ["   ", "   ", "   ", "   ", "   "],
+    }
+}
+
+/// Columns taken by `text` drawn with `scale`, including the gaps between characters.
+pub fn big_text_width(text: &str, scale: u16) -> u16 {
+    let pixels = text.chars().map(|c| glyph(c)[0].len() as u16).sum::<u16>();
+    let gaps = text.chars().count().saturating_sub(1) as u16;
+    (pixels + gaps) * PIXEL_WIDTH * scale
+}
+
+/// Largest scale at which `text` fits into `area`, at least 1.
+pub fn max_scale(text: &str, area: Rect) -> u16 {
+    let by_width = area.width / big_text_width(text, 1).max(1);
+    let by_height = area.height / BIG_TEXT_HEIGHT;
+    by_width.min(by_height).max(1)
+}
+
+/// Draws `text` in large block characters centered in `area`, pixels outside are clipped.
+pub fn render_big_text(text: &str, scale: u16, style: Style, area: Rect, buf: &mut Buffer) {
+    let width = big_text_width(text, scale);
+    let height = BIG_TEXT_HEIGHT * scale;
+    let left = area.x + area.width.saturating_sub(width) / 2;
+    let top = area.y + area.height.saturating_sub(height) / 2;
+    let mut x = left;
+    for c in text.chars() {
+        let rows = glyph(c);
+        for (row, pixels) in rows.iter().enumerate() {
+            for (column, pixel) in pixels.chars().enumerate() {
+                if pixel != '#' {
+                    continue;
+                }
+                let pixel_x = x + column as u16 * PIXEL_WIDTH * scale;
+                let pixel_y = top + row as u16 * scale;
+                fill(
+                    Rect::new(pixel_x, pixel_y, PIXEL_WIDTH * scale, scale),
+                    style,
+                    area,
+                    buf,
+                );
+            }
+        }
+        x += (rows[0].len() as u16 + 1) * PIXEL_WIDTH * scale;
+    }
+}
+
+fn fill(pixel: Rect, style: Style, area: Rect, buf: &mut Buffer) {
+    let pixel = pixel.intersection(area);
+    for y in pixel.top()..pixel.bottom() {
+        for x in pixel.left()..pixel.right() {
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                cell.set_symbol("█").set_style(style);
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::Utc;
+
+use crate::{client::Client, shared_data::SharedData};
+
+/// Order of the rows in the client overview, cycled with Tab.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SortColumn {
+    /// order of the hostnames in the config
+    #[default]
+    Room,
+    Name,
+    Ip,
+    Layer,
+    /// clients whose timer page was requested longest ago first
+    LastAccess,
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 5] = [
+        SortColumn::Room,
+        SortColumn::Name,
+        SortColumn::Ip,
+        SortColumn::Layer,
+        SortColumn::LastAccess,
+    ];
+
+    pub fn next(self) -> Self {
+        cycle(&Self::ALL, self, 1)
+    }
+
+    pub fn previous(self) -> Self {
+        cycle(&Self::ALL, self, Self::ALL.len() - 1)
+    }
+
+    /// header of the column the clients are sorted by
+    pub fn header(&self) -> Option<&'static str> {
+        match self {
+            SortColumn::Room => None,
+            SortColumn::Name => Some("name"),
+            SortColumn::Ip => Some("IP address"),
+            SortColumn::Layer => Some("kbd layer"),
+            SortColumn::LastAccess => Some("time since timer request"),
+        }
+    }
+}
+
+/// Subset of clients shown in the client overview, cycled with `/`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ClientFilter {
+    #[default]
+    All,
+    Unlocked,
+    Locked,
+    Unreachable,
+    /// timer page without heartbeat for longer than `tui_heartbeat_timeout_seconds`
+    StalePage,
+}
+
+impl ClientFilter {
+    const ALL: [ClientFilter; 5] = [
+        ClientFilter::All,
+        ClientFilter::Unlocked,
+        ClientFilter::Locked,
+        ClientFilter::Unreachable,
+        ClientFilter::StalePage,
+    ];
+
+    pub fn next(self) -> Self {
+        cycle(&Self::ALL, self, 1)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClientFilter::All => "all",
+            ClientFilter::Unlocked => "only unlocked",
+            ClientFilter::Locked => "only locked",
+            ClientFilter::Unreachable => "only unreachable",
+            ClientFilter::StalePage => "only stale timer page",
+        }
+    }
+
+    fn matches(&self, shared_data: &SharedData, client: &Client) -> bool {
+        let layer = client.current_layer.lock().unwrap().clone();
+        match self {
+            ClientFilter::All => true,
+            ClientFilter::Unlocked => layer.is_some_and(|layer| layer != "disabled"),
+            ClientFilter::Locked => layer.as_deref() == Some("disabled"),
+            ClientFilter::Unreachable => layer.is_none(),
+            ClientFilter::StalePage => match *client.last_heartbeat.lock().unwrap() {
+                Some(last_heartbeat) => {
+                    (Utc::now() - last_heartbeat).num_seconds()
+                        > shared_data.config.tui_heartbeat_timeout_seconds
+                }
+                None => true,
+            },
+        }
+    }
+}
+
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T, step: usize) -> T {
+    let index = all.iter().position(|item| *item == current).unwrap_or(0);
+    all[(index + step) % all.len()]
+}
+
+/// Indices into `shared_data.clients` in the order they are listed.
+pub fn visible_clients(
+    shared_data: &SharedData,
+    sort: SortColumn,
+    filter: ClientFilter,
+) -> Vec<usize> {
+    let mut indices = shared_data
+        .clients
+        .iter()
+        .enumerate()
+        .filter(|(_, client)| filter.matches(shared_data, client))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let clients = &shared_data.clients;
+    match sort {
+        SortColumn::Room => {}
+        SortColumn::Name => indices.sort_by_key(|&index| clients[index].name.clone()),
+        SortColumn::Ip => indices.sort_by_key(|&index| clients[index].ip_address),
+        // unreachable clients last
+        SortColumn::Layer => indices.sort_by_key(|&index| {
+            let layer = clients[index].current_layer.lock().unwrap().clone();
+            (layer.is_none(), layer)
+        }),
+        // never requested first, then the oldest request
+        SortColumn::LastAccess => {
+            indices.sort_by_key(|&index| *clients[index].last_timer_access.lock().unwrap())
+        }
+    }
+    indices
+}
@@ -0,0 +1,89 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::*,
+    symbols::border,
+    widgets::{block::*, *},
+};
+
+/// A single line text prompt shown as popup over the rest of the TUI.
+#[derive(Debug)]
+pub struct InputPrompt<P> {
+    pub purpose: P,
+    pub title: String,
+    pub text: String,
+}
+
+pub enum InputResult {
+    Editing,
+    Submitted(String),
+    Cancelled,
+}
+
+impl<P> InputPrompt<P> {
+    pub fn new(purpose: P, title: String) -> Self {
+        InputPrompt {
+            purpose,
+            title,
+            text: String::new(),
+        }
+    }
+
+    /// prompt that starts with `text` already entered
+    pub fn with_text(purpose: P, title: String, text: String) -> Self {
+        InputPrompt {
+            purpose,
+            title,
+            text,
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> InputResult {
+        match key_event.code {
+            KeyCode::Enter => InputResult::Submitted(self.text.trim().to_string()),
+            KeyCode::Esc => InputResult::Cancelled,
+            KeyCode::Backspace => {
+                self.text.pop();
+                InputResult::Editing
+            }
+            KeyCode::Char(c) => {
+                self.text.push(c);
+                InputResult::Editing
+            }
+            _ => InputResult::Editing,
+        }
+    }
+
+    /// Draws the prompt centered in `area`, `hint` is shown below the input line.
+    pub fn render(&self, hint: Option<Line>, area: Rect, buf: &mut Buffer) {
+        let width = area.width.saturating_sub(4).min(80);
+        let height = if hint.is_some() { 4 } else { 3 };
+        let popup = Rect::new(
+            area.x + (area.width.saturating_sub(width)) / 2,
+            area.y + (area.height.saturating_sub(height)) / 2,
+            width,
+            height.min(area.height),
+        );
+        let instructions = Title::from(vec![
+            " Confirm".into(),
+            " <Enter> ".blue().bold(),
+            " Cancel".into(),
+            " <Escape> ".blue().bold(),
+        ]);
+        let block = Block::default()
+            .title(Title::from(format!(" {} ", self.title).bold()).alignment(Alignment::Center))
+            .title(
+                instructions
+                    .alignment(Alignment::Center)
+                    .position(block::Position::Bottom),
+            )
+            .borders(Borders::ALL)
+            .border_set(border::THICK);
+
+        let mut lines = vec![Line::from(vec![self.text.clone().into(), "_".slow_blink()])];
+        lines.extend(hint);
+        Clear.render(popup, buf);
+        Paragraph::new(Text::from(lines))
+            .block(block)
+            .render(popup, buf);
+    }
+}
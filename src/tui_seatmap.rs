@@ -0,0 +1,143 @@
+use chrono::Utc;
+use ratatui::{
+    prelude::*,
+    symbols::border,
+    widgets::{block::*, *},
+};
+
+use crate::{client::Client, input_parser::RoomLayout, shared_data::SharedData};
+
+/// seats do not grow beyond this many lines in rooms with few rows
+const MAX_SEAT_HEIGHT: u16 = 6;
+
+/// Draws the room as a grid of seats, colored by keyboard layer and timer page state.
+pub fn render_seatmap(
+    shared_data: &SharedData,
+    layout: &RoomLayout,
+    selected_client: Option<usize>,
+    toggle_hint: Span<'static>,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let title = Title::from(" Seat Map ".bold());
+    let legend = Title::from(vec![
+        " enabled ".green().bold(),
+        " disabled ".red().bold(),
+        " unreachable ".dark_gray().bold(),
+        " Client list ".into(),
+        toggle_hint,
+    ]);
+    let block = Block::default()
+        .title(title.alignment(Alignment::Center))
+        .title(
+            legend
+                .alignment(Alignment::Center)
+                .position(block::Position::Bottom),
+        )
+        .borders(Borders::ALL)
+        .border_set(border::THICK);
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    let unplaced = shared_data
+        .clients
+        .iter()
+        .filter(|client| !layout.seats.contains_key(&client.name))
+        .map(|client| client.name.as_str())
+        .collect::<Vec<_>>();
+    let (grid_area, unplaced_area) = if unplaced.is_empty() {
+        (inner, None)
+    } else {
+        let chunks = Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).split(inner);
+        (chunks[0], Some(chunks[1]))
+    };
+
+    let seat_height = (grid_area.height / layout.rows.max(1)).clamp(3, MAX_SEAT_HEIGHT);
+    let rows = Layout::vertical(vec![Constraint::Length(seat_height); layout.rows as usize])
+        .split(grid_area);
+    for (index, client) in shared_data.clients.iter().enumerate() {
+        let Some(&(row, column)) = layout.seats.get(&client.name) else {
+            continue;
+        };
+        if row >= layout.rows || column >= layout.columns {
+            continue;
+        }
+        let columns = Layout::horizontal(vec![
+            Constraint::Ratio(1, layout.columns as u32);
+            layout.columns as usize
+        ])
+        .split(rows[row as usize]);
+        render_seat(
+            shared_data,
+            client,
+            Some(index) == selected_client,
+            columns[column as usize],
+            buf,
+        );
+    }
+
+    if let Some(unplaced_area) = unplaced_area {
+        Line::from(vec![
+            "Not placed in layout: ".into(),
+            unplaced.join(", ").yellow(),
+        ])
+        .render(unplaced_area, buf);
+    }
+}
+
+fn render_seat(
+    shared_data: &SharedData,
+    client: &Client,
+    selected: bool,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let layer = client.current_layer.lock().unwrap().clone();
+    let color = match layer.as_deref() {
+        Some("enabled") => Color::Green,
+        Some("disabled") => Color::Red,
+        Some(_) => Color::Yellow,
+        None => Color::DarkGray,
+    };
+    let border_set = if selected {
+        border::DOUBLE
+    } else {
+        border::PLAIN
+    };
+    let block = Block::default()
+        .title(Title::from(client.name.clone().bold()).alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_set(border_set)
+        .border_style(Style::new().fg(color));
+
+    let mut lines = vec![Line::from(layer.unwrap_or("---".to_string()).fg(color))];
+    if client.layer_drifted() {
+        lines.push(Line::from("LAYER DRIFT".white().bold().on_red()));
+    }
+    if let Some(student) = client.student.lock().unwrap().as_ref() {
+        lines.push(Line::from(student.name.clone().cyan()));
+    }
+    lines.push(page_state_line(shared_data, client));
+    let note_count = client.notes.lock().unwrap().len();
+    if note_count > 0 {
+        lines.push(Line::from(format!("✎ {}", note_count).magenta().bold()));
+    }
+
+    Paragraph::new(Text::from(lines))
+        .alignment(Alignment::Center)
+        .block(block)
+        .render(area, buf);
+}
+
+fn page_state_line(shared_data: &SharedData, client: &Client) -> Line<'static> {
+    match *client.last_heartbeat.lock().unwrap() {
+        Some(last_heartbeat)
+            if (Utc::now() - last_heartbeat).num_seconds()
+                > shared_data.config.tui_heartbeat_timeout_seconds =>
+        {
+            Line::from("NO PAGE".red().bold().reversed())
+        }
+        Some(_) => Line::from("page ok".green()),
+        None => Line::from("page never seen".dark_gray()),
+    }
+}
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    client::Client,
+    exam_record::RecordCategory,
+    kanata_tcp::{keeps_finished_layer, try_layer_change_all, try_layer_change_client},
+    persistance::{delete_persisted_time, persist_session, persist_time},
+    shared_data::SharedData,
+    symlinks::try_set_symlink_target_and_update,
+};
+
+/// State that a supervisor action changed, restoring it takes the action back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inverse {
+    Times(Option<(DateTime<Utc>, Duration)>),
+    TaskDescription(Option<String>),
+    /// intended layer of every client, by index into `shared_data.clients`, the observed
+    /// layer if nothing was intended yet
+    Layers(Vec<Option<String>>),
+    /// phase index, its start and when it was captured, to continue its countdown
+    Phase(usize, Option<DateTime<Utc>>, DateTime<Utc>),
+}
+
+/// A supervisor action that can be undone.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub description: String,
+    pub inverses: Vec<Inverse>,
+}
+
+pub fn capture_times(shared_data: &SharedData) -> Inverse {
+    Inverse::Times(*shared_data.times.lock().unwrap())
+}
+
+pub fn capture_task_description(shared_data: &SharedData) -> Inverse {
+    Inverse::TaskDescription(shared_data.symlink_target.lock().unwrap().clone())
+}
+
+pub fn capture_layers(shared_data: &SharedData) -> Inverse {
+    Inverse::Layers(
+        shared_data
+            .clients
+            .iter()
+            .map(|client| {
+                let intended_layer = client
+                    .intended_layer
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .map(|intended| intended.layer.clone());
+                // before the first layer change of the session only the observed one is known
+                intended_layer.or_else(|| client.current_layer.lock().unwrap().clone())
+            })
+            .collect(),
+    )
+}
+
+pub fn capture_phase(shared_data: &SharedData) -> Inverse {
+    Inverse::Phase(
+        *shared_data.phase.lock().unwrap(),
+        *shared_data.phase_started.lock().unwrap(),
+        Utc::now(),
+    )
+}
+
+/// The state `inverse` restores is still the current one, the action did not change it.
+pub fn is_unchanged(shared_data: &SharedData, inverse: &Inverse) -> bool {
+    match inverse {
+        Inverse::Times(_) => *inverse == capture_times(shared_data),
+        Inverse::TaskDescription(_) => *inverse == capture_task_description(shared_data),
+        Inverse::Layers(_) => *inverse == capture_layers(shared_data),
+        Inverse::Phase(index, started, _) => {
+            *index == *shared_data.phase.lock().unwrap()
+                && *started == *shared_data.phase_started.lock().unwrap()
+        }
+    }
+}
+
+/// Reason why `entry` cannot be taken back completely, undoing it is refused then.
+pub fn undo_refusal(shared_data: &SharedData, entry: &UndoEntry) -> Option<String> {
+    let resets_timer = entry.inverses.contains(&Inverse::Times(None));
+    for inverse in &entry.inverses {
+        match inverse {
+            Inverse::TaskDescription(None) => {
+                return Some("the task description was not set before".to_string());
+            }
+            Inverse::Layers(layers) if layers.iter().all(Option::is_none) => {
+                return Some("the keyboard layers were not known before".to_string());
+            }
+            Inverse::Phase(index, _, _) => {
+                let current = *shared_data.phase.lock().unwrap();
+                let phases = &shared_data.config.phases;
+                let left_phases = phases.get(index + 1..=current).unwrap_or_default();
+                if let Some(phase) = left_phases.iter().find(|phase| phase.collect_work) {
+                    return Some(format!("the work was collected in phase '{}'", phase.name));
+                }
+                let time_is_up = !resets_timer
+                    && shared_data
+                        .latest_finish_time()
+                        .is_some_and(|finish_time| Utc::now() >= finish_time);
+                let restored = phases.get(*index);
+                if time_is_up && restored.is_some_and(|phase| phase.advance_when_time_is_up) {
+                    return Some("the time is up, the phase would be left again".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Restores the state from before the action of `entry`.
+pub fn undo(shared_data: Arc<SharedData>, entry: UndoEntry) {
+    shared_data.record_event(
+        RecordCategory::Undo,
+        format!("undo of '{}'", entry.description),
+    );
+    for inverse in entry.inverses {
+        match inverse {
+            Inverse::Times(times) => {
+                *shared_data.times.lock().unwrap() = times;
+                match times {
+                    Some((start_time, duration)) => {
+                        persist_time(start_time, duration);
+                        shared_data.record_event(
+                            RecordCategory::Timer,
+                            format!("duration restored to {}min", duration.num_minutes()),
+                        );
+                    }
+                    None => {
+                        delete_persisted_time();
+                        shared_data.record_event(RecordCategory::Timer, "timer reset".to_string());
+                    }
+                }
+            }
+            Inverse::TaskDescription(Some(target)) => {
+                try_set_symlink_target_and_update(shared_data.clone(), &target);
+            }
+            // refused by undo_refusal, there is nothing to go back to
+            Inverse::TaskDescription(None) => {}
+            Inverse::Layers(layers) => restore_layers(&shared_data, layers),
+            Inverse::Phase(index, phase_started, captured) => {
+                *shared_data.phase.lock().unwrap() = index;
+                // the countdown of the phase continues where it was left instead of
+                // running out while the undone phase was active
+                *shared_data.phase_started.lock().unwrap() =
+                    phase_started.map(|started| started + (Utc::now() - captured));
+                persist_session(&shared_data);
+            }
+        }
+    }
+}
+
+fn restore_layers(shared_data: &Arc<SharedData>, layers: Vec<Option<String>>) {
+    // usually all keyboards shared a layer, which is restored with a single record entry
+    if let Some(Some(first)) = layers.first() {
+        if layers.iter().all(|layer| layer.as_ref() == Some(first)) {
+            try_layer_change_all(shared_data.clone(), first);
+            return;
+        }
+    }
+    for (client, layer) in clients_to_restore(shared_data, &layers) {
+        match layer {
+            Some(layer) => try_layer_change_client(shared_data, client, layer),
+            None => *client.intended_layer.lock().unwrap() = None,
+        }
+    }
+    persist_session(shared_data);
+}
+
+/// Clients with the layer they get back, finished ones are left alone like a change of
+/// all keyboards does.
+fn clients_to_restore<'a>(
+    shared_data: &'a SharedData,
+    layers: &'a [Option<String>],
+) -> Vec<(&'a Client, Option<&'a str>)> {
+    shared_data
+        .clients
+        .iter()
+        .zip(layers)
+        .map(|(client, layer)| (client, layer.as_deref()))
+        .filter(|(client, layer)| !layer.is_some_and(|layer| keeps_finished_layer(client, layer)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::{
+        exam_record::ExamRecord,
+        input_parser::{Config, SymlinkInfo},
+    };
+
+    fn shared_data_with_clients(count: u8) -> SharedData {
+        let config: Config =
+            serde_json::from_str(include_str!("../res/ppmngr_cfg_default.json")).unwrap();
+        let clients = (1..=count)
+            .map(|index| Client::new(format!("pc{}", index), Ipv4Addr::new(10, 0, 0, index)))
+            .collect();
+        let symlink_info = SymlinkInfo {
+            symlink_path: String::new(),
+            dummy_target: String::new(),
+            real_target: String::new(),
+            mode: Default::default(),
+        };
+        SharedData::new(
+            config,
+            "test".to_string(),
+            clients,
+            symlink_info,
+            None,
+            Vec::new(),
+            None,
+            ExamRecord::default(),
+        )
+    }
+
+    #[test]
+    fn undoes_a_disable_from_the_initial_state() {
+        let shared_data = shared_data_with_clients(2);
+        for client in &shared_data.clients {
+            *client.current_layer.lock().unwrap() = Some("enabled".to_string());
+        }
+        let inverse = capture_layers(&shared_data);
+        for client in &shared_data.clients {
+            client.intend_layer("disabled");
+        }
+        assert!(!is_unchanged(&shared_data, &inverse));
+        let entry = UndoEntry {
+            description: "disable keyboards".to_string(),
+            inverses: vec![inverse.clone()],
+        };
+        assert_eq!(undo_refusal(&shared_data, &entry), None);
+        let enabled = Some("enabled".to_string());
+        assert_eq!(inverse, Inverse::Layers(vec![enabled.clone(), enabled]));
+    }
+
+    #[test]
+    fn refuses_to_restore_unknown_layers() {
+        let shared_data = shared_data_with_clients(2);
+        let inverse = capture_layers(&shared_data);
+        assert_eq!(inverse, Inverse::Layers(vec![None, None]));
+        let entry = UndoEntry {
+            description: "disable keyboards".to_string(),
+            inverses: vec![inverse],
+        };
+        assert!(undo_refusal(&shared_data, &entry).is_some());
+    }
+
+    #[test]
+    fn leaves_finished_clients_disabled() {
+        let shared_data = shared_data_with_clients(3);
+        *shared_data.clients[1].finished.lock().unwrap() = Some(Utc::now());
+        let layers = vec![
+            Some("enabled".to_string()),
+            Some("enabled".to_string()),
+            Some("disabled".to_string()),
+        ];
+        let restored = clients_to_restore(&shared_data, &layers)
+            .into_iter()
+            .map(|(client, layer)| (client.name.as_str(), layer))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            restored,
+            vec![("pc1", Some("enabled")), ("pc3", Some("disabled"))]
+        );
+    }
+}